@@ -1,10 +1,15 @@
 //! BGP attribute structs
+use std::collections::HashSet;
+use std::error::Error;
 use std::fmt::{Display, Formatter};
 use std::net::IpAddr;
+use std::str::FromStr;
 use itertools::Itertools;
+use num_traits::FromPrimitive;
+use crate::err::BgpModelsError;
 use crate::network::*;
-use serde::{Serialize, Serializer};
-use crate::bgp::{ExtendedCommunity, LargeCommunity, Community};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use crate::bgp::{AspaValidationState, UpdateValidationError, ExtendedCommunity, FlowspecNlri, LargeCommunity, Community};
 
 /// The high-order bit (bit 0) of the Attribute Flags octet is the
 /// Optional bit.  It defines whether the attribute is optional (if
@@ -60,7 +65,6 @@ pub enum AttrType {
     ORIGINATOR_ID = 9,
     CLUSTER_LIST = 10,
     /// <https://tools.ietf.org/html/rfc4760>
-    CLUSTER_ID = 13,
     MP_REACHABLE_NLRI = 14,
     MP_UNREACHABLE_NLRI = 15,
     /// <https://datatracker.ietf.org/doc/html/rfc4360>
@@ -84,6 +88,155 @@ pub enum AttrType {
     DEVELOPMENT = 255,
 }
 
+/// Expected length of an attribute value in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectedLen {
+    Exact(usize),
+    OneOf(&'static [usize]),
+    Variable,
+}
+
+impl ExpectedLen {
+    /// Check whether a value length satisfies the expectation.
+    pub fn matches(&self, len: usize) -> bool {
+        match self {
+            ExpectedLen::Exact(v) => *v == len,
+            ExpectedLen::OneOf(v) => v.contains(&len),
+            ExpectedLen::Variable => true,
+        }
+    }
+}
+
+impl AttrType {
+    /// Check whether the attribute is well-known, i.e. its Optional flag bit must be 0.
+    ///
+    /// <https://datatracker.ietf.org/doc/html/rfc4271#section-5>
+    pub fn is_well_known(&self) -> bool {
+        matches!(self,
+            AttrType::ORIGIN |
+            AttrType::AS_PATH |
+            AttrType::NEXT_HOP |
+            AttrType::LOCAL_PREFERENCE |
+            AttrType::ATOMIC_AGGREGATE
+        )
+    }
+
+    /// Get the expected value length of the attribute type, so that parsers can validate the
+    /// length before decoding.
+    ///
+    /// `AGGREGATOR` is 6 bytes with 2-byte ASNs or 8 bytes with 4-byte ASNs. Returns `None` for
+    /// attribute types without modeled length rules.
+    pub fn expected_value_len(&self) -> Option<ExpectedLen> {
+        match self {
+            AttrType::ORIGIN => Some(ExpectedLen::Exact(1)),
+            AttrType::NEXT_HOP => Some(ExpectedLen::Exact(4)),
+            AttrType::MULTI_EXIT_DISCRIMINATOR => Some(ExpectedLen::Exact(4)),
+            AttrType::LOCAL_PREFERENCE => Some(ExpectedLen::Exact(4)),
+            AttrType::ATOMIC_AGGREGATE => Some(ExpectedLen::Exact(0)),
+            AttrType::AGGREGATOR => Some(ExpectedLen::OneOf(&[6, 8])),
+            AttrType::ORIGINATOR_ID => Some(ExpectedLen::Exact(4)),
+            AttrType::AS4_AGGREGATOR => Some(ExpectedLen::Exact(8)),
+            AttrType::AS_PATH |
+            AttrType::COMMUNITIES |
+            AttrType::CLUSTER_LIST |
+            AttrType::MP_REACHABLE_NLRI |
+            AttrType::MP_UNREACHABLE_NLRI |
+            AttrType::EXTENDED_COMMUNITIES |
+            AttrType::AS4_PATH |
+            AttrType::TUNNEL_ENCAPSULATION |
+            AttrType::IPV6_ADDRESS_SPECIFIC_EXTENDED_COMMUNITIES |
+            AttrType::LARGE_COMMUNITIES => Some(ExpectedLen::Variable),
+            _ => None,
+        }
+    }
+}
+
+/// Attribute types that have a modeled value in [AttributeValue].
+pub const SUPPORTED_ATTR_TYPES: &[AttrType] = &[
+    AttrType::ORIGIN,
+    AttrType::AS_PATH,
+    AttrType::NEXT_HOP,
+    AttrType::MULTI_EXIT_DISCRIMINATOR,
+    AttrType::LOCAL_PREFERENCE,
+    AttrType::ATOMIC_AGGREGATE,
+    AttrType::AGGREGATOR,
+    AttrType::COMMUNITIES,
+    AttrType::ORIGINATOR_ID,
+    AttrType::CLUSTER_LIST,
+    AttrType::MP_REACHABLE_NLRI,
+    AttrType::MP_UNREACHABLE_NLRI,
+    AttrType::EXTENDED_COMMUNITIES,
+    AttrType::AS4_PATH,
+    AttrType::AS4_AGGREGATOR,
+    AttrType::IPV6_ADDRESS_SPECIFIC_EXTENDED_COMMUNITIES,
+    AttrType::LARGE_COMMUNITIES,
+    AttrType::PMSI_TUNNEL,
+    AttrType::TUNNEL_ENCAPSULATION,
+    AttrType::DEVELOPMENT,
+];
+
+/// Deprecated attribute types.
+///
+/// These codes are marked as deprecated or historic by IANA and are not part of [AttrType].
+/// Use [parse_attr_type] to tell the deprecated codes apart from unassigned ones.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Primitive, PartialEq, Eq, Hash, Copy, Clone, Serialize)]
+pub enum DeprecatedAttrType {
+    DPA = 11,
+    ADVERTISER = 12,
+    RCID_PATH_CLUSTER_ID = 13,
+    SAFI_SPECIFIC_ATTRIBUTE = 19,
+    CONNECTOR_ATTRIBUTE = 20,
+    AS_PATHLIMIT = 21,
+    BGP_ENTROPY_LABEL_CAPABILITY = 28,
+    DEPRECATED_30 = 30,
+    DEPRECATED_31 = 31,
+    DEPRECATED_129 = 129,
+    DEPRECATED_241 = 241,
+    DEPRECATED_242 = 242,
+    DEPRECATED_243 = 243,
+}
+
+/// Error for parsing BGP attribute type code
+#[derive(Debug, PartialEq, Eq, Clone, Serialize)]
+pub enum AttrTypeParsingError {
+    Unassigned(u8),
+    DeprecatedCode(DeprecatedAttrType),
+}
+
+impl Display for AttrTypeParsingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttrTypeParsingError::Unassigned(v) => {
+                write!(f, "unassigned BGP attribute type code: {}", v)
+            }
+            AttrTypeParsingError::DeprecatedCode(v) => {
+                write!(f, "deprecated BGP attribute type code: {} ({:?})", *v as u8, v)
+            }
+        }
+    }
+}
+
+impl Error for AttrTypeParsingError{}
+
+/// Parse an attribute type code into [AttrType].
+///
+/// Deprecated codes are returned as [AttrTypeParsingError::DeprecatedCode] so that consumers can
+/// handle them explicitly instead of treating them as unknown.
+pub fn parse_attr_type(attr_type_code: &u8) -> Result<AttrType, AttrTypeParsingError> {
+    match AttrType::from_u8(*attr_type_code) {
+        Some(v) => {
+            Ok(v)
+        }
+        None => {
+            match DeprecatedAttrType::from_u8(*attr_type_code) {
+                Some(v) => Err(AttrTypeParsingError::DeprecatedCode(v)),
+                None => Err(AttrTypeParsingError::Unassigned(*attr_type_code)),
+            }
+        }
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Debug, Primitive, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum Origin {
@@ -100,15 +253,96 @@ pub enum AtomicAggregate {
 }
 
 /// BGP Attribute struct with attribute value and flag
-#[derive(Debug, PartialEq, Clone, Serialize, Eq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Eq, Hash)]
 pub struct Attribute {
     pub attr_type: AttrType,
     pub value: AttributeValue,
     pub flag: u8,
 }
 
+impl Attribute {
+    /// Check whether the Optional and Transitive bits of the flag match the attribute type.
+    ///
+    /// Well-known attributes must have the Optional bit unset and the Transitive bit set, and all
+    /// other attributes must have the Optional bit set
+    /// ([RFC 4271 section 5](https://datatracker.ietf.org/doc/html/rfc4271#section-5)).
+    pub fn has_valid_flags(&self) -> bool {
+        let optional = AttributeFlagsBit::OptionalBit as u8;
+        let transitive = AttributeFlagsBit::TransitiveBit as u8;
+        match self.attr_type.is_well_known() {
+            true => self.flag & (optional | transitive) == transitive,
+            false => self.flag & optional != 0,
+        }
+    }
+}
+
+/// Report every attribute whose flags do not match its type, see [Attribute::has_valid_flags].
+pub fn check_attribute_flags(attributes: &[Attribute]) -> Vec<UpdateValidationError> {
+    attributes.iter()
+        .filter(|attr| !attr.has_valid_flags())
+        .map(|attr| UpdateValidationError::AttributeFlagsError(attr.attr_type))
+        .collect()
+}
+
+/// Typed accessors for a list of attributes.
+///
+/// Each method finds the attribute of the matching type and returns its value, or `None` if the
+/// attribute is not present. If an attribute type appears more than once, the first one is used.
+pub trait AttributesExt {
+    fn origin(&self) -> Option<Origin>;
+    fn as_path(&self) -> Option<&AsPath>;
+    fn next_hop(&self) -> Option<IpAddr>;
+    fn med(&self) -> Option<u32>;
+    fn local_pref(&self) -> Option<u32>;
+    fn communities(&self) -> Option<&[Community]>;
+}
+
+impl AttributesExt for [Attribute] {
+    fn origin(&self) -> Option<Origin> {
+        self.iter().find_map(|attr| match &attr.value {
+            AttributeValue::Origin(v) => Some(*v),
+            _ => None,
+        })
+    }
+
+    fn as_path(&self) -> Option<&AsPath> {
+        self.iter().find_map(|attr| match &attr.value {
+            AttributeValue::AsPath(v) => Some(v),
+            _ => None,
+        })
+    }
+
+    fn next_hop(&self) -> Option<IpAddr> {
+        self.iter().find_map(|attr| match &attr.value {
+            AttributeValue::NextHop(v) => Some(*v),
+            _ => None,
+        })
+    }
+
+    fn med(&self) -> Option<u32> {
+        self.iter().find_map(|attr| match &attr.value {
+            AttributeValue::MultiExitDiscriminator(v) => Some(*v),
+            _ => None,
+        })
+    }
+
+    fn local_pref(&self) -> Option<u32> {
+        self.iter().find_map(|attr| match &attr.value {
+            AttributeValue::LocalPreference(v) => Some(*v),
+            _ => None,
+        })
+    }
+
+    fn communities(&self) -> Option<&[Community]> {
+        self.iter().find_map(|attr| match &attr.value {
+            AttributeValue::Communities(v) => Some(v.as_slice()),
+            _ => None,
+        })
+    }
+}
+
 /// The `AttributeValue` enum represents different kinds of Attribute values.
-#[derive(Debug, PartialEq, Clone, Serialize, Eq)]
+#[derive(Debug, PartialEq, Clone, Serialize, Eq, Hash)]
 pub enum AttributeValue {
     Origin(Origin),
     AsPath(AsPath),
@@ -126,6 +360,8 @@ pub enum AttributeValue {
     MpReachNlri(Nlri),
     MpUnreachNlri(Nlri),
     Development(Vec<u8>),
+    PmsiTunnel(PmsiTunnel),
+    TunnelEncap(TunnelEncap),
 }
 
 /////////////
@@ -133,7 +369,7 @@ pub enum AttributeValue {
 /////////////
 
 /// Enum of AS path segment.
-#[derive(Debug, PartialEq, Clone, Eq)]
+#[derive(Debug, PartialEq, Clone, Eq, Hash)]
 pub enum AsPathSegment {
     AsSequence(Vec<Asn>),
     AsSet(Vec<Asn>),
@@ -153,7 +389,7 @@ impl AsPathSegment {
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Eq)]
+#[derive(Debug, PartialEq, Clone, Eq, Default, Hash)]
 pub struct AsPath {
     pub segments: Vec<AsPathSegment>,
 }
@@ -171,6 +407,42 @@ impl AsPath {
         self.segments.push(segment);
     }
 
+    /// Prepend `asn` to the path `count` times.
+    ///
+    /// The ASNs are inserted into the leading `AsSequence` segment. If the path is empty or
+    /// starts with any other segment type, a new leading `AsSequence` is created.
+    pub fn prepend_asn(&mut self, asn: Asn, count: usize) {
+        if count == 0 {
+            return
+        }
+        match self.segments.first_mut() {
+            Some(AsPathSegment::AsSequence(v)) => {
+                v.splice(0..0, vec![asn; count]);
+            }
+            _ => {
+                self.segments.insert(0, AsPathSegment::AsSequence(vec![asn; count]));
+            }
+        }
+    }
+
+    /// Append `asn` to the path `count` times.
+    ///
+    /// The ASNs are appended to the trailing `AsSequence` segment. If the path is empty or
+    /// ends with any other segment type, a new trailing `AsSequence` is created.
+    pub fn append_asn(&mut self, asn: Asn, count: usize) {
+        if count == 0 {
+            return
+        }
+        match self.segments.last_mut() {
+            Some(AsPathSegment::AsSequence(v)) => {
+                v.extend(vec![asn; count]);
+            }
+            _ => {
+                self.segments.push(AsPathSegment::AsSequence(vec![asn; count]));
+            }
+        }
+    }
+
     pub fn segments(&self) -> &Vec<AsPathSegment> {
         &self.segments
     }
@@ -179,6 +451,194 @@ impl AsPath {
         self.segments.iter().map(AsPathSegment::count_asns).sum()
     }
 
+    /// Flatten the path into an ordered list of ASN hops.
+    ///
+    /// `AsSequence` segments are included in order, and every member of an `AsSet` is included
+    /// in its listed order. Confederation segments are excluded since they are not part of the
+    /// public path.
+    pub fn to_hops(&self) -> Vec<Asn> {
+        self.segments.iter().flat_map(|seg| match seg {
+            AsPathSegment::AsSequence(v) | AsPathSegment::AsSet(v) => v.as_slice(),
+            AsPathSegment::ConfedSequence(_) | AsPathSegment::ConfedSet(_) => &[],
+        }).copied().collect()
+    }
+
+    /// Iterate over every ASN of the path in order, including members of sets and confederation
+    /// segments.
+    pub fn iter_asns(&self) -> impl Iterator<Item = Asn> + '_ {
+        self.segments.iter().flat_map(|seg| match seg {
+            AsPathSegment::AsSequence(v) | AsPathSegment::AsSet(v) |
+            AsPathSegment::ConfedSequence(v) | AsPathSegment::ConfedSet(v) => v.iter().copied()
+        })
+    }
+
+    /// Iterate over the ASNs of the path in order, excluding confederation segments.
+    ///
+    /// This yields the same ASNs as [AsPath::to_hops] without allocating.
+    pub fn iter_public_asns(&self) -> impl Iterator<Item = Asn> + '_ {
+        self.segments.iter().flat_map(|seg| match seg {
+            AsPathSegment::AsSequence(v) | AsPathSegment::AsSet(v) => v.as_slice(),
+            AsPathSegment::ConfedSequence(_) | AsPathSegment::ConfedSet(_) => &[],
+        }).copied()
+    }
+
+    /// Same as [AsPath::to_hops] with consecutive duplicate ASNs (prepending) collapsed.
+    pub fn dedup_prepends(&self) -> Vec<Asn> {
+        let mut hops = self.to_hops();
+        hops.dedup();
+        hops
+    }
+
+    /// Check whether the given ASN appears in any segment of the path, including confederation
+    /// segments.
+    pub fn contains_asn(&self, asn: Asn) -> bool {
+        self.segments.iter().any(|seg| match seg {
+            AsPathSegment::AsSequence(v) | AsPathSegment::AsSet(v) |
+            AsPathSegment::ConfedSequence(v) | AsPathSegment::ConfedSet(v) => v.contains(&asn)
+        })
+    }
+
+    /// Check whether the path contains a potential routing loop.
+    ///
+    /// A loop is an ASN that appears again after a different ASN in between. Consecutive repeats of
+    /// the same ASN are prepending and are not considered a loop.
+    pub fn contains_loop(&self) -> bool {
+        let mut seen: HashSet<u32> = HashSet::new();
+        let mut last: Option<u32> = None;
+        for seg in &self.segments {
+            let v = match seg {
+                AsPathSegment::AsSequence(v) | AsPathSegment::AsSet(v) |
+                AsPathSegment::ConfedSequence(v) | AsPathSegment::ConfedSet(v) => v
+            };
+            for asn in v {
+                if last == Some(asn.asn) {
+                    continue
+                }
+                if !seen.insert(asn.asn) {
+                    return true
+                }
+                last = Some(asn.asn);
+            }
+        }
+        false
+    }
+
+    /// Apply `f` to every ASN of the path, keeping the segment structure.
+    pub fn map_asns<F: Fn(Asn) -> Asn>(&self, f: F) -> AsPath {
+        let segments = self.segments.iter().map(|seg| {
+            let map = |v: &Vec<Asn>| v.iter().map(|asn| f(*asn)).collect();
+            match seg {
+                AsPathSegment::AsSequence(v) => AsPathSegment::AsSequence(map(v)),
+                AsPathSegment::AsSet(v) => AsPathSegment::AsSet(map(v)),
+                AsPathSegment::ConfedSequence(v) => AsPathSegment::ConfedSequence(map(v)),
+                AsPathSegment::ConfedSet(v) => AsPathSegment::ConfedSet(map(v)),
+            }
+        }).collect();
+        AsPath { segments }
+    }
+
+    /// Format the path with one labeled line per segment, for debugging.
+    ///
+    /// ```text
+    /// SEQ: 1 2 3
+    /// SET: {4,5}
+    /// CONFED_SEQ: (65001)
+    /// CONFED_SET: [65002,65003]
+    /// ```
+    pub fn debug_format(&self) -> String {
+        self.segments.iter().map(|seg| match seg {
+            AsPathSegment::AsSequence(v) => format!("SEQ: {}", v.iter().join(" ")),
+            AsPathSegment::AsSet(v) => format!("SET: {{{}}}", v.iter().join(",")),
+            AsPathSegment::ConfedSequence(v) => format!("CONFED_SEQ: ({})", v.iter().join(" ")),
+            AsPathSegment::ConfedSet(v) => format!("CONFED_SET: [{}]", v.iter().join(",")),
+        }).join("\n")
+    }
+
+    /// Format the path for display with at most `max_asns` leading ASNs, followed by `...` and
+    /// the origin of the path, e.g. `1 2 3 ... 65000`.
+    ///
+    /// Segments keep their `Display` markers, so a truncated set or confederation segment is
+    /// still wrapped in `{..}`, `(..)` or `[..]`. The origin is the last ASN when the path ends
+    /// with an `AsSequence`, or the whole last segment otherwise (e.g. `1 2 ... {7,8}`).
+    ///
+    /// Paths that fit without truncation are formatted in full, same as `Display`.
+    pub fn truncate_display(&self, max_asns: usize) -> String {
+        let members = |seg: &AsPathSegment| match seg {
+            AsPathSegment::AsSequence(v) | AsPathSegment::AsSet(v) |
+            AsPathSegment::ConfedSequence(v) | AsPathSegment::ConfedSet(v) => v.len()
+        };
+        let origin = match self.segments.last() {
+            Some(AsPathSegment::AsSequence(v)) if !v.is_empty() => {
+                AsPathSegment::AsSequence(v[v.len()-1..].to_vec())
+            }
+            Some(seg) => seg.clone(),
+            None => return String::new(),
+        };
+        if self.iter_asns().count() - members(&origin) <= max_asns {
+            return self.to_string()
+        }
+
+        let mut remaining = max_asns;
+        let mut head = AsPath::new();
+        for seg in &self.segments {
+            if remaining == 0 {
+                break
+            }
+            let take = |v: &Vec<Asn>| v.iter().take(remaining).copied().collect();
+            let seg = match seg {
+                AsPathSegment::AsSequence(v) => AsPathSegment::AsSequence(take(v)),
+                AsPathSegment::AsSet(v) => AsPathSegment::AsSet(take(v)),
+                AsPathSegment::ConfedSequence(v) => AsPathSegment::ConfedSequence(take(v)),
+                AsPathSegment::ConfedSet(v) => AsPathSegment::ConfedSet(take(v)),
+            };
+            remaining -= members(&seg);
+            head.add_segment(seg);
+        }
+
+        let origin = AsPath::from_segments(vec![origin]);
+        if head.segments.is_empty() {
+            format!("... {}", origin)
+        } else {
+            format!("{} ... {}", head, origin)
+        }
+    }
+
+    /// Verify the path with ASPA upstream verification, for routes received from a customer or
+    /// a lateral peer.
+    ///
+    /// `provider_lookup` returns the provider ASNs attested by the ASPA record of an ASN, or
+    /// `None` if the ASN has no ASPA record. Starting from the origin, every hop must be towards
+    /// a provider of the previous ASN:
+    ///
+    /// - `Invalid` if a hop is towards an ASN that is not an attested provider, or the path
+    ///   contains an AS_SET;
+    /// - `Unknown` if no hop is invalid but some ASN has no ASPA record, or the path is empty;
+    /// - `Valid` otherwise.
+    ///
+    /// Prepends are collapsed and confederation segments are ignored.
+    ///
+    /// <https://datatracker.ietf.org/doc/draft-ietf-sidrops-aspa-verification/>
+    pub fn verify_aspa<F: Fn(Asn) -> Option<Vec<Asn>>>(&self, provider_lookup: F) -> AspaValidationState {
+        if self.segments.iter().any(|seg| matches!(seg, AsPathSegment::AsSet(_))) {
+            return AspaValidationState::Invalid
+        }
+        let hops = self.dedup_prepends();
+        if hops.is_empty() {
+            return AspaValidationState::Unknown
+        }
+
+        let mut state = AspaValidationState::Valid;
+        // hops are ordered from the neighbor to the origin, each pair is (provider, customer)
+        for pair in hops.windows(2) {
+            match provider_lookup(pair[1]) {
+                Some(providers) if providers.contains(&pair[0]) => {}
+                Some(_) => return AspaValidationState::Invalid,
+                None => state = AspaValidationState::Unknown,
+            }
+        }
+        state
+    }
+
     /// Construct AsPath from AS_PATH and AS4_PATH
     ///
     /// https://datatracker.ietf.org/doc/html/rfc6793#section-4.2.3
@@ -227,21 +687,138 @@ impl AsPath {
         Some(AsPath{ segments: new_segs })
     }
 
+    /// Get the originating ASN(s) of the path.
+    ///
+    /// Returns a single ASN if the path ends with an `AsSequence`, or all members of a trailing
+    /// `AsSet`. Confederation segments at the tail of the path are skipped. Returns `None` for an
+    /// empty path.
+    pub fn origin(&self) -> Option<Vec<Asn>> {
+        self.segments.iter().rev().find_map(|seg| match seg {
+            AsPathSegment::AsSequence(v) => v.last().map(|n| vec![*n]),
+            AsPathSegment::AsSet(v) => Some(v.clone()),
+            AsPathSegment::ConfedSequence(_) | AsPathSegment::ConfedSet(_) => None,
+        })
+    }
+
+    /// Get the originating ASN(s) of the path. Same as [AsPath::origin].
     pub fn get_origin(&self) -> Option<Vec<Asn>> {
-        if let Some(seg) = self.segments.last() {
-            match seg {
-                AsPathSegment::AsSequence(v) => {
-                    if let Some(n) = v.last() {
-                        Some(vec![n.clone()])
-                    } else {
-                        None
-                    }
-                }
-                AsPathSegment::AsSet(v) => { Some(v.clone()) }
-                AsPathSegment::ConfedSequence(_) | AsPathSegment::ConfedSet(_) => { None }
-            }
-        } else {
-            None
+        self.origin()
+    }
+}
+
+/////////////////
+// PMSI TUNNEL //
+/////////////////
+
+/// P-Multicast Service Interface (PMSI) Tunnel attribute.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc6514#section-5>
+///
+/// ```text
+///   +---------------------------------+
+///   |  Flags (1 octet)                |
+///   +---------------------------------+
+///   |  Tunnel Type (1 octets)         |
+///   +---------------------------------+
+///   |  MPLS Label (3 octets)          |
+///   +---------------------------------+
+///   |  Tunnel Identifier (variable)   |
+///   +---------------------------------+
+/// ```
+///
+/// `mpls_label` holds the 20-bit label value, i.e. the high-order 20 bits of the 3-octet field.
+#[derive(Debug, PartialEq, Clone, Serialize, Eq, Hash)]
+pub struct PmsiTunnel {
+    pub flags: u8,
+    pub tunnel_type: u8,
+    pub mpls_label: u32,
+    pub tunnel_id: Vec<u8>,
+}
+
+impl PmsiTunnel {
+    /// Check whether the Leaf Information Required flag is set.
+    pub fn leaf_info_required(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    /// Get the name of the tunnel type.
+    ///
+    /// <https://www.iana.org/assignments/bgp-parameters/bgp-parameters.xhtml#pmsi-tunnel-types>
+    pub fn tunnel_type_name(&self) -> &'static str {
+        match self.tunnel_type {
+            0 => "no-tunnel-info",
+            1 => "rsvp-te-p2mp-lsp",
+            2 => "mldp-p2mp-lsp",
+            3 => "pim-ssm-tree",
+            4 => "pim-sm-tree",
+            5 => "bidir-pim-tree",
+            6 => "ingress-replication",
+            7 => "mldp-mp2mp-lsp",
+            _ => "unknown",
+        }
+    }
+}
+
+//////////////////
+// TUNNEL ENCAP //
+//////////////////
+
+/// Tunnel Encapsulation attribute.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc9012#section-2>
+///
+/// The attribute is a list of Tunnel TLVs, each of which carries a list of sub-TLVs. Sub-TLV
+/// values are kept as raw bytes so that no information is lost.
+#[derive(Debug, PartialEq, Clone, Serialize, Eq, Hash)]
+pub struct TunnelEncap {
+    pub tunnels: Vec<TunnelEncapTlv>,
+}
+
+/// A Tunnel TLV of the Tunnel Encapsulation attribute.
+///
+/// ```text
+///   +-----------------------------------+
+///   |  Tunnel Type (2 octets)           |
+///   +-----------------------------------+
+///   |  Length (2 octets)                |
+///   +-----------------------------------+
+///   |  Sub-TLVs (variable)              |
+///   +-----------------------------------+
+/// ```
+#[derive(Debug, PartialEq, Clone, Serialize, Eq, Hash)]
+pub struct TunnelEncapTlv {
+    pub tunnel_type: u16,
+    pub sub_tlvs: Vec<TunnelEncapSubTlv>,
+}
+
+/// A sub-TLV of a Tunnel TLV.
+///
+/// Sub-TLV types 0-127 have a 1-octet length field and types 128-255 have a 2-octet length field;
+/// only the type and value are stored here.
+#[derive(Debug, PartialEq, Clone, Serialize, Eq, Hash)]
+pub struct TunnelEncapSubTlv {
+    pub sub_tlv_type: u8,
+    pub value: Vec<u8>,
+}
+
+impl TunnelEncapTlv {
+    /// Get the name of the tunnel type.
+    ///
+    /// <https://www.iana.org/assignments/bgp-parameters/bgp-parameters.xhtml#tunnel-types>
+    pub fn tunnel_type_name(&self) -> Option<&'static str> {
+        match self.tunnel_type {
+            1 => Some("l2tpv3"),
+            2 => Some("gre"),
+            7 => Some("ip-in-ip"),
+            8 => Some("vxlan"),
+            9 => Some("nvgre"),
+            10 => Some("mpls"),
+            11 => Some("mpls-in-gre"),
+            12 => Some("vxlan-gpe"),
+            13 => Some("mpls-in-udp"),
+            15 => Some("sr-policy"),
+            19 => Some("geneve"),
+            _ => None,
         }
     }
 }
@@ -250,12 +827,44 @@ impl AsPath {
 // NLRI //
 //////////
 
-#[derive(Debug, PartialEq, Clone, Serialize, Eq)]
+/// NLRI of MP_REACH_NLRI and MP_UNREACH_NLRI attributes.
+///
+/// For [Safi::Flowspec] and [Safi::FlowspecVpn], the rules are in `flowspec` and `prefixes` is
+/// empty. `flowspec` is empty for other SAFIs.
+#[derive(Debug, PartialEq, Clone, Serialize, Eq, Hash)]
 pub struct Nlri {
     pub afi: Afi,
     pub safi: Safi,
     pub next_hop: Option<NextHopAddress>,
     pub prefixes: Vec<NetworkPrefix>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub flowspec: Vec<FlowspecNlri>,
+}
+
+impl Nlri {
+    /// Get the next hop of the NLRI as an [IpAddr].
+    ///
+    /// For a link-local next hop ([NextHopAddress::Ipv6LinkLocal]), the global address is
+    /// returned and the link-local address is dropped.
+    pub fn effective_next_hop(&self) -> Option<IpAddr> {
+        self.next_hop.map(IpAddr::from)
+    }
+}
+
+/// Resolve the effective next hop from a list of attributes.
+///
+/// The next hop in MP_REACH_NLRI is preferred over the NEXT_HOP attribute, see
+/// [Nlri::effective_next_hop].
+pub fn resolve_next_hop(attrs: &[Attribute]) -> Option<IpAddr> {
+    attrs.iter()
+        .find_map(|attr| match &attr.value {
+            AttributeValue::MpReachNlri(nlri) => nlri.effective_next_hop(),
+            _ => None,
+        })
+        .or_else(|| attrs.iter().find_map(|attr| match &attr.value {
+            AttributeValue::NextHop(v) => Some(*v),
+            _ => None,
+        }))
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize)]
@@ -289,9 +898,9 @@ pub struct MpReachableNlriV2 {
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct MpUnreachableNlri {
-    afi: Afi,
-    safi: Safi,
-    prefixes: Vec<NetworkPrefix>,
+    pub afi: Afi,
+    pub safi: Safi,
+    pub prefixes: Vec<NetworkPrefix>,
 }
 
 impl MpUnreachableNlri {
@@ -329,18 +938,47 @@ impl Display for AtomicAggregate {
 }
 
 
+/// Display the next hop address.
+///
+/// A link-local next hop pair is displayed as `global%linklocal`, e.g. `2001:db8::1%fe80::1`.
 impl Display for NextHopAddress {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}",
                match self {
                    NextHopAddress::Ipv4(v) => {v.to_string()}
                    NextHopAddress::Ipv6(v) => {v.to_string()}
-                   NextHopAddress::Ipv6LinkLocal(v1, _v2) => {v1.to_string()}
+                   NextHopAddress::Ipv6LinkLocal(v1, v2) => {format!("{}%{}", v1, v2)}
                }
         )
     }
 }
 
+impl Display for PmsiTunnel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} flags={} label={} id={}",
+               self.tunnel_type_name(),
+               self.flags,
+               self.mpls_label,
+               self.tunnel_id.iter().map(|b| format!("{:02X}", b)).join("")
+        )
+    }
+}
+
+impl Display for TunnelEncapTlv {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.tunnel_type_name() {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "tunnel-type-{}", self.tunnel_type),
+        }
+    }
+}
+
+impl Display for TunnelEncap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.tunnels.iter().join(" "))
+    }
+}
+
 impl Display for AsPath {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         write!(f, "{}",
@@ -348,22 +986,138 @@ impl Display for AsPath {
                    .segments()
                    .iter()
                    .map(|seg| match seg {
-                       AsPathSegment::AsSequence(v) | AsPathSegment::ConfedSequence(v) => v
+                       AsPathSegment::AsSequence(v) => v
                            .iter()
                            .join(" "),
-                       AsPathSegment::AsSet(v) | AsPathSegment::ConfedSet(v) => {
+                       AsPathSegment::AsSet(v) => {
                            format!(
                                "{{{}}}",
                                v.iter()
                                    .join(",")
                            )
                        }
+                       AsPathSegment::ConfedSequence(v) => format!("({})", v.iter().join(" ")),
+                       AsPathSegment::ConfedSet(v) => format!("[{}]", v.iter().join(",")),
                    })
                    .join(" ")
         )
     }
 }
 
+/////////////
+// FROMSTR //
+/////////////
+
+impl FromStr for Origin {
+    type Err = BgpModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "IGP" => Ok(Origin::IGP),
+            "EGP" => Ok(Origin::EGP),
+            "INCOMPLETE" => Ok(Origin::INCOMPLETE),
+            _ => Err(BgpModelsError::AttributeParsingError(format!("unknown origin {}", s))),
+        }
+    }
+}
+
+impl FromStr for AtomicAggregate {
+    type Err = BgpModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "NAG" => Ok(AtomicAggregate::NAG),
+            "AG" => Ok(AtomicAggregate::AG),
+            _ => Err(BgpModelsError::AttributeParsingError(format!("unknown atomic aggregate {}", s))),
+        }
+    }
+}
+
+/// Parse a next hop address from the format produced by its `Display` implementation.
+impl FromStr for NextHopAddress {
+    type Err = BgpModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || BgpModelsError::AttributeParsingError(format!("invalid next hop address {}", s));
+        let mut parts = s.splitn(2, '%');
+        match (parts.next(), parts.next()) {
+            (Some(global), Some(link_local)) => {
+                Ok(NextHopAddress::Ipv6LinkLocal(
+                    global.parse().map_err(|_| invalid())?,
+                    link_local.parse().map_err(|_| invalid())?,
+                ))
+            }
+            _ => {
+                match IpAddr::from_str(s).map_err(|_| invalid())? {
+                    IpAddr::V4(v) => Ok(NextHopAddress::Ipv4(v)),
+                    IpAddr::V6(v) => Ok(NextHopAddress::Ipv6(v)),
+                }
+            }
+        }
+    }
+}
+
+/// Parse an AS path from the format produced by its `Display` implementation, e.g.
+/// `(65001 65002) [65003,65004] 1 2 3 {4,5}`.
+///
+/// Space-separated ASNs form `AsSequence` segments and `{...}` forms `AsSet` segments.
+/// Confederation segments use `(...)` for `ConfedSequence` with space-separated ASNs and `[...]`
+/// for `ConfedSet` with comma-separated ASNs.
+impl FromStr for AsPath {
+    type Err = BgpModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_asn = |v: &str| -> Result<Asn, BgpModelsError> {
+            v.trim().parse::<u32>().map(Asn::from)
+                .map_err(|_| BgpModelsError::AttributeParsingError(format!("invalid ASN {} in AS path {}", v, s)))
+        };
+        let parse_asns = |v: &str, separator: char| -> Result<Vec<Asn>, BgpModelsError> {
+            match separator {
+                ',' => v.split(',').map(parse_asn).collect(),
+                _ => v.split_whitespace().map(parse_asn).collect(),
+            }
+        };
+
+        let mut path = AsPath::new();
+        let mut sequence: Vec<Asn> = vec![];
+        let mut rest = s.trim_start();
+        while let Some(first) = rest.chars().next() {
+            let group = match first {
+                '{' => Some(('}', ',')),
+                '(' => Some((')', ' ')),
+                '[' => Some((']', ',')),
+                _ => None,
+            };
+            match group {
+                Some((close, separator)) => {
+                    let end = rest.find(close).ok_or_else(|| BgpModelsError::AttributeParsingError(
+                        format!("unclosed {} in AS path {}", first, s)))?;
+                    if !sequence.is_empty() {
+                        path.add_segment(AsPathSegment::AsSequence(std::mem::take(&mut sequence)));
+                    }
+                    let asns = parse_asns(&rest[1..end], separator)?;
+                    path.add_segment(match first {
+                        '{' => AsPathSegment::AsSet(asns),
+                        '(' => AsPathSegment::ConfedSequence(asns),
+                        _ => AsPathSegment::ConfedSet(asns),
+                    });
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                    sequence.push(parse_asn(&rest[..end])?);
+                    rest = &rest[end..];
+                }
+            }
+            rest = rest.trim_start();
+        }
+        if !sequence.is_empty() {
+            path.add_segment(AsPathSegment::AsSequence(sequence));
+        }
+        Ok(path)
+    }
+}
+
 ///////////////
 // SERIALIZE //
 ///////////////
@@ -386,9 +1140,34 @@ impl Serialize for AtomicAggregate {
     }
 }
 
+/////////////////
+// DESERIALIZE //
+/////////////////
+
+impl<'de> Deserialize<'de> for AsPath {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let s = String::deserialize(deserializer)?;
+        AsPath::from_str(s.as_str()).map_err(de::Error::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for Origin {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let s = String::deserialize(deserializer)?;
+        Origin::from_str(s.as_str()).map_err(de::Error::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for AtomicAggregate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let s = String::deserialize(deserializer)?;
+        AtomicAggregate::from_str(s.as_str()).map_err(de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::bgp::attributes::{AsPath, AsPathSegment};
+    use crate::bgp::attributes::*;
 
     #[test]
     fn test_aspath_as4path_merge() {
@@ -423,4 +1202,431 @@ mod tests {
         assert!(origins.is_some());
         assert_eq!(origins.unwrap(), vec![7,8]);
     }
+
+    #[test]
+    fn test_origin() {
+        assert_eq!(AsPath::new().origin(), None);
+
+        let aspath = AsPath::from_segments(vec![
+            AsPathSegment::AsSequence([1,2,3].map(|i|{i.into()}).to_vec()),
+        ]);
+        assert_eq!(aspath.origin(), Some(vec![3.into()]));
+
+        let aspath = AsPath::from_segments(vec![
+            AsPathSegment::AsSequence([1,2,3].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::AsSet([7,8].map(|i|{i.into()}).to_vec()),
+        ]);
+        assert_eq!(aspath.origin(), Some(vec![7.into(), 8.into()]));
+
+        let aspath = AsPath::from_segments(vec![
+            AsPathSegment::ConfedSequence([65001].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::AsSequence([1,2,3].map(|i|{i.into()}).to_vec()),
+        ]);
+        assert_eq!(aspath.origin(), Some(vec![3.into()]));
+
+        let aspath = AsPath::from_segments(vec![
+            AsPathSegment::AsSequence([1,2,3].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::ConfedSet([65001].map(|i|{i.into()}).to_vec()),
+        ]);
+        assert_eq!(aspath.origin(), Some(vec![3.into()]));
+        assert_eq!(aspath.get_origin(), aspath.origin());
+    }
+
+    #[test]
+    fn test_contains_asn() {
+        let aspath = AsPath::from_segments(vec![
+            AsPathSegment::ConfedSequence([65001].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::AsSequence([1,2,3].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::AsSet([7,8].map(|i|{i.into()}).to_vec()),
+        ]);
+        assert!(aspath.contains_asn(65001.into()));
+        assert!(aspath.contains_asn(2.into()));
+        assert!(aspath.contains_asn(8.into()));
+        assert!(!aspath.contains_asn(4.into()));
+    }
+
+    #[test]
+    fn test_contains_loop() {
+        // prepending only
+        let aspath = AsPath::from_segments(vec![
+            AsPathSegment::AsSequence([1,2,2,2,3,3].map(|i|{i.into()}).to_vec()),
+        ]);
+        assert!(!aspath.contains_loop());
+
+        // genuine loop
+        let aspath = AsPath::from_segments(vec![
+            AsPathSegment::AsSequence([1,2,3,2].map(|i|{i.into()}).to_vec()),
+        ]);
+        assert!(aspath.contains_loop());
+    }
+
+    #[test]
+    fn test_to_hops() {
+        let aspath = AsPath::from_segments(vec![
+            AsPathSegment::ConfedSequence([65001].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::AsSequence([1,2,2,2,3].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::AsSet([7,8].map(|i|{i.into()}).to_vec()),
+        ]);
+        let hops: Vec<Asn> = [1,2,2,2,3,7,8].map(|i|{i.into()}).to_vec();
+        assert_eq!(aspath.to_hops(), hops);
+        let hops: Vec<Asn> = [1,2,3,7,8].map(|i|{i.into()}).to_vec();
+        assert_eq!(aspath.dedup_prepends(), hops);
+    }
+
+    #[test]
+    fn test_supported_attr_types() {
+        assert!(SUPPORTED_ATTR_TYPES.contains(&AttrType::ORIGIN));
+        assert!(SUPPORTED_ATTR_TYPES.contains(&AttrType::AS_PATH));
+    }
+
+    #[test]
+    fn test_prepend_append() {
+        let mut aspath = AsPath::new();
+        aspath.prepend_asn(1.into(), 2);
+        assert_eq!(aspath.count_asns(), 2);
+        assert_eq!(aspath.segments, vec![AsPathSegment::AsSequence([1,1].map(|i|{i.into()}).to_vec())]);
+
+        aspath.prepend_asn(2.into(), 1);
+        aspath.append_asn(3.into(), 1);
+        assert_eq!(aspath.count_asns(), 4);
+        assert_eq!(aspath.segments, vec![AsPathSegment::AsSequence([2,1,1,3].map(|i|{i.into()}).to_vec())]);
+
+        // leading set is not mutated
+        let mut aspath = AsPath::from_segments(vec![
+            AsPathSegment::AsSet([7,8].map(|i|{i.into()}).to_vec()),
+        ]);
+        aspath.prepend_asn(1.into(), 3);
+        assert_eq!(aspath.count_asns(), 4);
+        assert_eq!(aspath.segments, vec![
+            AsPathSegment::AsSequence([1,1,1].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::AsSet([7,8].map(|i|{i.into()}).to_vec()),
+        ]);
+        aspath.append_asn(9.into(), 1);
+        assert_eq!(aspath.count_asns(), 5);
+        assert_eq!(aspath.segments.last(), Some(&AsPathSegment::AsSequence(vec![9.into()])));
+    }
+
+    #[test]
+    fn test_mp_unreachable_nlri_fields() {
+        let prefixes = vec![NetworkPrefix::from_str("2001:db8::/32").unwrap()];
+        let nlri = MpUnreachableNlri::new(Afi::Ipv6, Safi::Unicast, prefixes.clone());
+        assert_eq!(nlri.afi, Afi::Ipv6);
+        assert_eq!(nlri.safi, Safi::Unicast);
+        assert_eq!(nlri.prefixes, prefixes);
+    }
+
+    #[test]
+    fn test_attributes_ext() {
+        let attrs = [
+            Attribute { attr_type: AttrType::ORIGIN, value: AttributeValue::Origin(Origin::IGP), flag: 0x40 },
+            Attribute { attr_type: AttrType::AS_PATH, value: AttributeValue::AsPath(AsPath::from_str("1 2 3").unwrap()), flag: 0x40 },
+            Attribute { attr_type: AttrType::NEXT_HOP, value: AttributeValue::NextHop(IpAddr::from_str("10.0.0.1").unwrap()), flag: 0x40 },
+            Attribute { attr_type: AttrType::MULTI_EXIT_DISCRIMINATOR, value: AttributeValue::MultiExitDiscriminator(0), flag: 0x80 },
+            Attribute { attr_type: AttrType::LOCAL_PREFERENCE, value: AttributeValue::LocalPreference(100), flag: 0x40 },
+            Attribute { attr_type: AttrType::COMMUNITIES, value: AttributeValue::Communities(vec![Community::NoExport]), flag: 0xc0 },
+        ];
+        assert_eq!(attrs.origin(), Some(Origin::IGP));
+        assert_eq!(attrs.as_path(), Some(&AsPath::from_str("1 2 3").unwrap()));
+        assert_eq!(attrs.next_hop(), Some(IpAddr::from_str("10.0.0.1").unwrap()));
+        assert_eq!(attrs.med(), Some(0));
+        assert_eq!(attrs.local_pref(), Some(100));
+        assert_eq!(attrs.communities(), Some([Community::NoExport].as_ref()));
+
+        let empty: Vec<Attribute> = vec![];
+        assert_eq!(empty.origin(), None);
+        assert_eq!(empty.as_path(), None);
+        assert_eq!(empty.next_hop(), None);
+        assert_eq!(empty.med(), None);
+        assert_eq!(empty.local_pref(), None);
+        assert_eq!(empty.communities(), None);
+    }
+
+    #[test]
+    fn test_check_attribute_flags() {
+        let origin = Attribute {
+            attr_type: AttrType::ORIGIN,
+            value: AttributeValue::Origin(Origin::IGP),
+            flag: 0x40,
+        };
+        assert!(origin.has_valid_flags());
+        assert!(check_attribute_flags(std::slice::from_ref(&origin)).is_empty());
+
+        let optional_origin = Attribute { flag: 0xc0, ..origin.clone() };
+        assert!(!optional_origin.has_valid_flags());
+        assert_eq!(check_attribute_flags(&[origin, optional_origin]),
+                   vec![UpdateValidationError::AttributeFlagsError(AttrType::ORIGIN)]);
+    }
+
+    #[test]
+    fn test_aspath_from_str() {
+        let aspath = AsPath::from_segments(vec![
+            AsPathSegment::AsSequence([1,2,3].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::AsSet([7,8].map(|i|{i.into()}).to_vec()),
+        ]);
+        assert_eq!(aspath.to_string(), "1 2 3 {7,8}");
+        assert_eq!(AsPath::from_str("1 2 3 {7,8}").unwrap(), aspath);
+        assert_eq!(AsPath::from_str("").unwrap(), AsPath::new());
+        assert!(AsPath::from_str("1 a 3").is_err());
+    }
+
+    #[test]
+    fn test_aspath_confed_str() {
+        let aspath = AsPath::from_segments(vec![
+            AsPathSegment::ConfedSequence([65001,65002].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::ConfedSet([65003,65004].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::AsSequence([1,2,3].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::AsSet([7,8].map(|i|{i.into()}).to_vec()),
+        ]);
+        assert_eq!(aspath.to_string(), "(65001 65002) [65003,65004] 1 2 3 {7,8}");
+        assert_eq!(AsPath::from_str(aspath.to_string().as_str()).unwrap(), aspath);
+        assert!(AsPath::from_str("(65001 65002 1 2").is_err());
+    }
+
+    #[test]
+    fn test_next_hop_str() {
+        let link_local = NextHopAddress::Ipv6LinkLocal(
+            "2001:db8::1".parse().unwrap(),
+            "fe80::1".parse().unwrap(),
+        );
+        assert_eq!(link_local.to_string(), "2001:db8::1%fe80::1");
+        assert_eq!(NextHopAddress::from_str("2001:db8::1%fe80::1").unwrap(), link_local);
+
+        let v4 = NextHopAddress::Ipv4("10.0.0.1".parse().unwrap());
+        assert_eq!(v4.to_string(), "10.0.0.1");
+        assert_eq!(NextHopAddress::from_str("10.0.0.1").unwrap(), v4);
+
+        let v6 = NextHopAddress::Ipv6("2001:db8::1".parse().unwrap());
+        assert_eq!(v6.to_string(), "2001:db8::1");
+        assert_eq!(NextHopAddress::from_str("2001:db8::1").unwrap(), v6);
+
+        assert!(NextHopAddress::from_str("10.0.0.1%fe80::1").is_err());
+    }
+
+    #[test]
+    fn test_expected_value_len() {
+        assert_eq!(AttrType::ORIGIN.expected_value_len(), Some(ExpectedLen::Exact(1)));
+        assert_eq!(AttrType::AGGREGATOR.expected_value_len(), Some(ExpectedLen::OneOf(&[6, 8])));
+        assert_eq!(AttrType::AS_PATH.expected_value_len(), Some(ExpectedLen::Variable));
+        assert_eq!(AttrType::DEVELOPMENT.expected_value_len(), None);
+
+        assert!(ExpectedLen::Exact(1).matches(1));
+        assert!(!ExpectedLen::Exact(1).matches(2));
+        assert!(ExpectedLen::OneOf(&[6, 8]).matches(8));
+        assert!(!ExpectedLen::OneOf(&[6, 8]).matches(7));
+    }
+
+    #[test]
+    fn test_pmsi_tunnel() {
+        let tunnel = PmsiTunnel {
+            flags: 1,
+            tunnel_type: 6,
+            mpls_label: 100,
+            tunnel_id: vec![10, 0, 0, 1],
+        };
+        assert!(tunnel.leaf_info_required());
+        assert_eq!(tunnel.tunnel_type_name(), "ingress-replication");
+        assert_eq!(tunnel.to_string(), "ingress-replication flags=1 label=100 id=0A000001");
+
+        let attr = Attribute {
+            attr_type: AttrType::PMSI_TUNNEL,
+            value: AttributeValue::PmsiTunnel(tunnel.clone()),
+            flag: 0xc0,
+        };
+        assert_eq!(attr.value, AttributeValue::PmsiTunnel(tunnel));
+    }
+
+    #[test]
+    fn test_tunnel_encap() {
+        let encap = TunnelEncap {
+            tunnels: vec![
+                TunnelEncapTlv {
+                    tunnel_type: 8,
+                    sub_tlvs: vec![TunnelEncapSubTlv { sub_tlv_type: 6, value: vec![0, 0, 0, 1] }],
+                },
+                TunnelEncapTlv { tunnel_type: 100, sub_tlvs: vec![] },
+            ]
+        };
+        assert_eq!(encap.tunnels[0].tunnel_type_name(), Some("vxlan"));
+        assert_eq!(encap.tunnels[1].tunnel_type_name(), None);
+        assert_eq!(encap.to_string(), "vxlan tunnel-type-100");
+        assert_eq!(encap.tunnels[0].sub_tlvs[0].value, vec![0, 0, 0, 1]);
+
+        let attr = Attribute {
+            attr_type: AttrType::TUNNEL_ENCAPSULATION,
+            value: AttributeValue::TunnelEncap(encap.clone()),
+            flag: 0xc0,
+        };
+        assert_eq!(attr.value, AttributeValue::TunnelEncap(encap));
+        assert_eq!(AttrType::TUNNEL_ENCAPSULATION.expected_value_len(), Some(ExpectedLen::Variable));
+    }
+
+    #[test]
+    fn test_aspath_iter_asns() {
+        let aspath = AsPath{
+            segments: vec![
+                AsPathSegment::ConfedSequence([65001].map(|i|{i.into()}).to_vec()),
+                AsPathSegment::AsSequence([1,2,3].map(|i|{i.into()}).to_vec()),
+                AsPathSegment::AsSet([4,5].map(|i|{i.into()}).to_vec()),
+            ]
+        };
+        let expected: Vec<Asn> = [65001,1,2,3,4,5].map(|i|{i.into()}).to_vec();
+        assert_eq!(aspath.iter_asns().collect::<Vec<Asn>>(), expected);
+        let public: Vec<Asn> = aspath.iter_public_asns().collect();
+        assert_eq!(public, expected[1..].to_vec());
+        assert_eq!(public, aspath.to_hops());
+        assert_eq!(AsPath::new().iter_asns().count(), 0);
+    }
+
+    #[test]
+    fn test_aspath_map_asns() {
+        let aspath = AsPath{
+            segments: vec![
+                AsPathSegment::ConfedSequence([65001].map(|i|{i.into()}).to_vec()),
+                AsPathSegment::AsSequence([1,2,3].map(|i|{i.into()}).to_vec()),
+                AsPathSegment::AsSet([4,5].map(|i|{i.into()}).to_vec()),
+            ]
+        };
+        let mapped = aspath.map_asns(|asn| (asn.asn + 1).into());
+        assert_eq!(mapped, AsPath{
+            segments: vec![
+                AsPathSegment::ConfedSequence([65002].map(|i|{i.into()}).to_vec()),
+                AsPathSegment::AsSequence([2,3,4].map(|i|{i.into()}).to_vec()),
+                AsPathSegment::AsSet([5,6].map(|i|{i.into()}).to_vec()),
+            ]
+        });
+    }
+
+    #[test]
+    fn test_aspath_debug_format() {
+        let aspath = AsPath{
+            segments: vec![
+                AsPathSegment::ConfedSequence([65001].map(|i|{i.into()}).to_vec()),
+                AsPathSegment::ConfedSet([65002,65003].map(|i|{i.into()}).to_vec()),
+                AsPathSegment::AsSequence([1,2,3].map(|i|{i.into()}).to_vec()),
+                AsPathSegment::AsSet([4,5].map(|i|{i.into()}).to_vec()),
+            ]
+        };
+        assert_eq!(
+            aspath.debug_format(),
+            "CONFED_SEQ: (65001)\nCONFED_SET: [65002,65003]\nSEQ: 1 2 3\nSET: {4,5}"
+        );
+        assert_eq!(AsPath::new().debug_format(), "");
+    }
+
+    #[test]
+    fn test_aspath_verify_aspa() {
+        let providers = |asn: Asn| -> Option<Vec<Asn>> {
+            match asn.asn {
+                65003 => Some(vec![65002.into()]),
+                65002 => Some(vec![65001.into(), 65010.into()]),
+                65001 => Some(vec![]),
+                _ => None,
+            }
+        };
+        let path = |asns: Vec<u32>| AsPath::from_segments(vec![
+            AsPathSegment::AsSequence(asns.into_iter().map(|i| i.into()).collect()),
+        ]);
+
+        assert_eq!(path(vec![65001, 65002, 65003]).verify_aspa(providers), AspaValidationState::Valid);
+        assert_eq!(path(vec![65001, 65001, 65002, 65003, 65003]).verify_aspa(providers), AspaValidationState::Valid);
+        // 65003 only attests 65002 as provider
+        assert_eq!(path(vec![65001, 65004, 65003]).verify_aspa(providers), AspaValidationState::Invalid);
+        // 65004 has no ASPA record
+        assert_eq!(path(vec![65002, 65003, 65004]).verify_aspa(providers), AspaValidationState::Unknown);
+        assert_eq!(path(vec![65003]).verify_aspa(providers), AspaValidationState::Valid);
+        assert_eq!(AsPath::new().verify_aspa(providers), AspaValidationState::Unknown);
+
+        let with_set = AsPath::from_segments(vec![
+            AsPathSegment::AsSequence([65002].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::AsSet([65003].map(|i|{i.into()}).to_vec()),
+        ]);
+        assert_eq!(with_set.verify_aspa(providers), AspaValidationState::Invalid);
+    }
+
+    #[test]
+    fn test_aspath_truncate_display() {
+        let mut asns: Vec<u32> = (1..50).collect();
+        asns.push(65000);
+        let aspath = AsPath::from_segments(vec![
+            AsPathSegment::AsSequence(asns.into_iter().map(|i| i.into()).collect()),
+        ]);
+        assert_eq!(aspath.truncate_display(3), "1 2 3 ... 65000");
+        assert_eq!(aspath.truncate_display(0), "... 65000");
+        assert_eq!(aspath.truncate_display(49), aspath.to_string());
+
+        let short = AsPath::from_segments(vec![
+            AsPathSegment::AsSequence([1,2,3].map(|i|{i.into()}).to_vec()),
+        ]);
+        assert_eq!(short.truncate_display(3), "1 2 3");
+        assert_eq!(AsPath::new().truncate_display(3), "");
+
+        let with_set = AsPath::from_segments(vec![
+            AsPathSegment::ConfedSequence([65001,65002].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::AsSequence([1,2,3].map(|i|{i.into()}).to_vec()),
+            AsPathSegment::AsSet([7,8].map(|i|{i.into()}).to_vec()),
+        ]);
+        assert_eq!(with_set.truncate_display(1), "(65001) ... {7,8}");
+        assert_eq!(with_set.truncate_display(3), "(65001 65002) 1 ... {7,8}");
+        assert_eq!(with_set.truncate_display(5), with_set.to_string());
+    }
+
+    #[test]
+    fn test_resolve_next_hop() {
+        let next_hop = Attribute {
+            attr_type: AttrType::NEXT_HOP,
+            value: AttributeValue::NextHop(IpAddr::from_str("10.0.0.1").unwrap()),
+            flag: 0x40,
+        };
+        let mp_reach = |next_hop: NextHopAddress| Attribute {
+            attr_type: AttrType::MP_REACHABLE_NLRI,
+            value: AttributeValue::MpReachNlri(Nlri {
+                afi: Afi::Ipv6,
+                safi: Safi::Unicast,
+                next_hop: Some(next_hop),
+                prefixes: vec![NetworkPrefix::from_str("2001:db8::/32").unwrap()],
+                flowspec: vec![],
+            }),
+            flag: 0x80,
+        };
+        let global = NextHopAddress::from_str("2001:db8::1").unwrap();
+        let link_local = NextHopAddress::from_str("2001:db8::1%fe80::1").unwrap();
+
+        assert_eq!(resolve_next_hop(&[]), None);
+        assert_eq!(resolve_next_hop(std::slice::from_ref(&next_hop)), Some(IpAddr::from_str("10.0.0.1").unwrap()));
+        assert_eq!(
+            resolve_next_hop(&[next_hop.clone(), mp_reach(global)]),
+            Some(IpAddr::from_str("2001:db8::1").unwrap())
+        );
+        assert_eq!(
+            resolve_next_hop(&[mp_reach(link_local), next_hop]),
+            Some(IpAddr::from_str("2001:db8::1").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_attr_type() {
+        assert_eq!(parse_attr_type(&1), Ok(AttrType::ORIGIN));
+        assert_eq!(parse_attr_type(&10), Ok(AttrType::CLUSTER_LIST));
+
+        let deprecated = [
+            (11, DeprecatedAttrType::DPA),
+            (12, DeprecatedAttrType::ADVERTISER),
+            (13, DeprecatedAttrType::RCID_PATH_CLUSTER_ID),
+            (19, DeprecatedAttrType::SAFI_SPECIFIC_ATTRIBUTE),
+            (20, DeprecatedAttrType::CONNECTOR_ATTRIBUTE),
+            (21, DeprecatedAttrType::AS_PATHLIMIT),
+            (28, DeprecatedAttrType::BGP_ENTROPY_LABEL_CAPABILITY),
+            (30, DeprecatedAttrType::DEPRECATED_30),
+            (31, DeprecatedAttrType::DEPRECATED_31),
+            (129, DeprecatedAttrType::DEPRECATED_129),
+            (241, DeprecatedAttrType::DEPRECATED_241),
+            (242, DeprecatedAttrType::DEPRECATED_242),
+            (243, DeprecatedAttrType::DEPRECATED_243),
+        ];
+        for (code, t) in deprecated {
+            assert_eq!(parse_attr_type(&code), Err(AttrTypeParsingError::DeprecatedCode(t)));
+        }
+
+        assert_eq!(parse_attr_type(&100), Err(AttrTypeParsingError::Unassigned(100)));
+    }
 }
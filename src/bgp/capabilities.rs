@@ -2,6 +2,7 @@ use serde::Serialize;
 use num_traits::FromPrimitive;
 use std::error::Error;
 use std::fmt::{Display, Formatter};
+use crate::network::{Afi, Asn, Safi};
 
 /// BGP capability parsing error
 #[derive(Debug, PartialEq, Eq, Clone, Serialize)]
@@ -55,10 +56,190 @@ pub enum BgpCapabilityType {
     FQDN_CAPABILITY = 73,
 }
 
+/// Capability codes recognized by [BgpCapabilityType].
+pub const SUPPORTED_CAPABILITIES: &[u8] = &[
+    1, 2, 3, 5, 6, 7, 8, 9, 64, 65, 67, 68, 69, 70, 71, 72, 73,
+];
+
+/// Capability value decoded into a structured form, see [Capability::decode](crate::bgp::Capability::decode).
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub enum DecodedCapability {
+    /// Multiprotocol Extensions, code 1 ([RFC 4760](https://datatracker.ietf.org/doc/html/rfc4760#section-8)).
+    Multiprotocol { afi: Afi, safi: Safi },
+    /// Route Refresh, code 2 ([RFC 2918](https://datatracker.ietf.org/doc/html/rfc2918#section-2)).
+    RouteRefresh,
+    /// Graceful Restart, code 64 ([RFC 4724](https://datatracker.ietf.org/doc/html/rfc4724#section-3)).
+    GracefulRestart(GracefulRestartCapability),
+    /// Support for 4-octet AS number, code 65 ([RFC 6793](https://datatracker.ietf.org/doc/html/rfc6793#section-3)).
+    FourOctetAsn(Asn),
+    /// ADD-PATH, code 69 ([RFC 7911](https://datatracker.ietf.org/doc/html/rfc7911#section-4)).
+    AddPath(AddPathCapability),
+    /// Value of a capability that is not decoded, or that is malformed.
+    Raw(Vec<u8>),
+}
+
+impl DecodedCapability {
+    /// Decode a capability value by its code.
+    ///
+    /// Unknown codes and values that do not match the expected length or contain unknown
+    /// AFI/SAFI are kept as [DecodedCapability::Raw].
+    pub fn decode(code: u8, value: &[u8]) -> DecodedCapability {
+        let decoded = match (BgpCapabilityType::from_u8(code), value) {
+            (Some(BgpCapabilityType::MULTIPROTOCOL_EXTENSIONS_FOR_BGP_4), [afi_1, afi_2, _, safi]) => {
+                match (Afi::from_u16(u16::from_be_bytes([*afi_1, *afi_2])), Safi::from_u8(*safi)) {
+                    (Some(afi), Some(safi)) => Some(DecodedCapability::Multiprotocol { afi, safi }),
+                    _ => None,
+                }
+            }
+            (Some(BgpCapabilityType::ROUTE_REFRESH_CAPABILITY_FOR_BGP_4), []) => {
+                Some(DecodedCapability::RouteRefresh)
+            }
+            (Some(BgpCapabilityType::GRACEFUL_RESTART_CAPABILITY), _) => {
+                GracefulRestartCapability::decode(value).map(DecodedCapability::GracefulRestart)
+            }
+            (Some(BgpCapabilityType::SUPPORT_FOR_4_OCTET_AS_NUMBER_CAPABILITY), [a, b, c, d]) => {
+                Some(DecodedCapability::FourOctetAsn(u32::from_be_bytes([*a, *b, *c, *d]).into()))
+            }
+            (Some(BgpCapabilityType::ADD_PATH_CAPABILITY), _) => {
+                AddPathCapability::decode(value).map(DecodedCapability::AddPath)
+            }
+            _ => None,
+        };
+        decoded.unwrap_or_else(|| DecodedCapability::Raw(value.to_vec()))
+    }
+}
+
+/// Graceful Restart capability.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc4724#section-3>
+///
+/// ```text
+///   +--------------------------------------------------+
+///   | Restart Flags (4 bits)                           |
+///   +--------------------------------------------------+
+///   | Restart Time in seconds (12 bits)                |
+///   +--------------------------------------------------+
+///   | Address Family Identifier (16 bits)              |
+///   +--------------------------------------------------+
+///   | Subsequent Address Family Identifier (8 bits)    |
+///   +--------------------------------------------------+
+///   | Flags for Address Family (8 bits)                |
+///   +--------------------------------------------------+
+///   | ...                                              |
+///   +--------------------------------------------------+
+/// ```
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct GracefulRestartCapability {
+    /// The 4-bit restart flags.
+    pub restart_flags: u8,
+    pub restart_time: u16,
+    pub families: Vec<GracefulRestartFamily>,
+}
+
+/// Address family entry of [GracefulRestartCapability].
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct GracefulRestartFamily {
+    pub afi: Afi,
+    pub safi: Safi,
+    pub flags: u8,
+}
+
+impl GracefulRestartCapability {
+    /// Decode the capability value. Returns `None` if the value is malformed or contains unknown
+    /// AFI/SAFI.
+    pub fn decode(value: &[u8]) -> Option<GracefulRestartCapability> {
+        let (header, rest) = (value.get(..2)?, value.get(2..)?);
+        let chunks = rest.chunks_exact(4);
+        if !chunks.remainder().is_empty() {
+            return None
+        }
+        let families = chunks.map(|c| {
+            Some(GracefulRestartFamily {
+                afi: Afi::from_u16(u16::from_be_bytes([c[0], c[1]]))?,
+                safi: Safi::from_u8(c[2])?,
+                flags: c[3],
+            })
+        }).collect::<Option<Vec<GracefulRestartFamily>>>()?;
+        Some(GracefulRestartCapability {
+            restart_flags: header[0] >> 4,
+            restart_time: u16::from_be_bytes([header[0] & 0x0f, header[1]]),
+            families,
+        })
+    }
+
+    /// Check whether the Restart State (R) bit is set, i.e. the speaker has restarted.
+    pub fn restart_state(&self) -> bool {
+        self.restart_flags & 0x08 != 0
+    }
+}
+
+impl GracefulRestartFamily {
+    /// Check whether the Forwarding State (F) bit is set, i.e. forwarding state for the address
+    /// family has been preserved during the restart.
+    pub fn forwarding_state_preserved(&self) -> bool {
+        self.flags & 0x80 != 0
+    }
+}
+
+/// ADD-PATH capability.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc7911#section-4>
+///
+/// ```text
+///   +------------------------------------------------+
+///   | Address Family Identifier (2 octets)           |
+///   +------------------------------------------------+
+///   | Subsequent Address Family Identifier (1 octet) |
+///   +------------------------------------------------+
+///   | Send/Receive (1 octet)                         |
+///   +------------------------------------------------+
+///   | ...                                            |
+///   +------------------------------------------------+
+/// ```
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct AddPathCapability {
+    pub entries: Vec<AddPathEntry>,
+}
+
+/// Address family entry of [AddPathCapability].
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct AddPathEntry {
+    pub afi: Afi,
+    pub safi: Safi,
+    pub direction: AddPathDirection,
+}
+
+/// Whether the speaker is able to receive and/or send multiple paths for an address family.
+#[derive(Debug, Primitive, Clone, Copy, Serialize, PartialEq, Eq)]
+pub enum AddPathDirection {
+    Receive = 1,
+    Send = 2,
+    Both = 3,
+}
+
+impl AddPathCapability {
+    /// Decode the capability value. Returns `None` if the value is malformed or contains unknown
+    /// AFI/SAFI or Send/Receive values.
+    pub fn decode(value: &[u8]) -> Option<AddPathCapability> {
+        let chunks = value.chunks_exact(4);
+        if !chunks.remainder().is_empty() {
+            return None
+        }
+        let entries = chunks.map(|c| {
+            Some(AddPathEntry {
+                afi: Afi::from_u16(u16::from_be_bytes([c[0], c[1]]))?,
+                safi: Safi::from_u8(c[2])?,
+                direction: AddPathDirection::from_u8(c[3])?,
+            })
+        }).collect::<Option<Vec<AddPathEntry>>>()?;
+        Some(AddPathCapability { entries })
+    }
+}
+
 pub fn parse_capability(capability_code: &u8) -> Result<BgpCapabilityType, BgpCapabilityParsingError> {
     match BgpCapabilityType::from_u8(*capability_code) {
         Some(v) => {
-            return Ok(v)
+            Ok(v)
         }
         None => {
             if [4, 66, 128, 129, 130, 131, 184, 185].contains(capability_code) {
@@ -143,6 +324,70 @@ mod tests {
         assert_eq!(parse_capability(&code), Ok(BgpCapabilityType::ROUTING_POLICY_DISTRIBUTION));
         code = 73;
         assert_eq!(parse_capability(&code), Ok(BgpCapabilityType::FQDN_CAPABILITY));
+    }
+
+    #[test]
+    fn test_decode_capability() {
+        assert_eq!(
+            DecodedCapability::decode(65, &[0, 0, 0xfd, 0xe8]),
+            DecodedCapability::FourOctetAsn(65000.into())
+        );
+        assert_eq!(
+            DecodedCapability::decode(65, &[0xfa, 0x56, 0xea, 0x00]),
+            DecodedCapability::FourOctetAsn(4200000000u32.into())
+        );
+        assert_eq!(
+            DecodedCapability::decode(1, &[0, 2, 0, 1]),
+            DecodedCapability::Multiprotocol { afi: Afi::Ipv6, safi: Safi::Unicast }
+        );
+        assert_eq!(DecodedCapability::decode(2, &[]), DecodedCapability::RouteRefresh);
+
+        // malformed length and unknown codes are kept raw
+        assert_eq!(DecodedCapability::decode(65, &[0, 1]), DecodedCapability::Raw(vec![0, 1]));
+        assert_eq!(DecodedCapability::decode(200, &[1, 2, 3]), DecodedCapability::Raw(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_decode_graceful_restart() {
+        // restart state set, restart time 120, IPv4 unicast with forwarding state preserved
+        let decoded = DecodedCapability::decode(64, &[0x80, 0x78, 0, 1, 1, 0x80]);
+        let gr = match decoded {
+            DecodedCapability::GracefulRestart(gr) => gr,
+            v => panic!("unexpected capability {:?}", v),
+        };
+        assert!(gr.restart_state());
+        assert_eq!(gr.restart_time, 120);
+        assert_eq!(gr.families, vec![GracefulRestartFamily { afi: Afi::Ipv4, safi: Safi::Unicast, flags: 0x80 }]);
+        assert!(gr.families[0].forwarding_state_preserved());
+
+        // no address families
+        let gr = GracefulRestartCapability::decode(&[0x00, 0xb4]).unwrap();
+        assert!(!gr.restart_state());
+        assert_eq!(gr.restart_time, 180);
+        assert!(gr.families.is_empty());
+
+        assert_eq!(DecodedCapability::decode(64, &[0x80, 0x78, 0, 1]), DecodedCapability::Raw(vec![0x80, 0x78, 0, 1]));
+    }
+
+    #[test]
+    fn test_decode_add_path() {
+        let decoded = DecodedCapability::decode(69, &[0, 2, 1, 3, 0, 1, 1, 1]);
+        assert_eq!(decoded, DecodedCapability::AddPath(AddPathCapability {
+            entries: vec![
+                AddPathEntry { afi: Afi::Ipv6, safi: Safi::Unicast, direction: AddPathDirection::Both },
+                AddPathEntry { afi: Afi::Ipv4, safi: Safi::Unicast, direction: AddPathDirection::Receive },
+            ]
+        }));
+
+        // invalid Send/Receive value
+        assert_eq!(DecodedCapability::decode(69, &[0, 2, 1, 4]), DecodedCapability::Raw(vec![0, 2, 1, 4]));
+    }
+
+    #[test]
+    fn test_supported_capabilities() {
+        for code in SUPPORTED_CAPABILITIES {
+            assert!(parse_capability(code).is_ok());
+        }
 
     }
 }
@@ -1,17 +1,94 @@
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt::Formatter;
 use enum_primitive_derive::Primitive;
+use num_traits::FromPrimitive;
 use std::net::{Ipv4Addr, Ipv6Addr};
-use serde::Serialize;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use serde::ser::SerializeStruct;
+use crate::err::BgpModelsError;
 use crate::network::Asn;
 
-#[derive(Debug, PartialEq, Copy, Clone, Eq)]
+/// Community of any kind.
+///
+/// Communities are ordered by kind first (regular < extended < IPv6 address specific extended <
+/// large), then by their content. [ExtendedCommunity::Raw] communities are 8-byte extended
+/// communities and sort after the typed extended communities, before the IPv6 address specific
+/// ones.
+#[derive(Debug, PartialEq, Copy, Clone, Eq, Hash)]
 pub enum MetaCommunity {
     Community(Community),
     ExtendedCommunity(ExtendedCommunity),
     LargeCommunity(LargeCommunity),
 }
 
-#[derive(Debug, PartialEq, Copy, Clone, Eq)]
+impl PartialOrd for MetaCommunity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MetaCommunity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.kind_rank().cmp(&other.kind_rank()).then_with(|| match (self, other) {
+            (MetaCommunity::Community(a), MetaCommunity::Community(b)) => a.cmp(b),
+            (MetaCommunity::ExtendedCommunity(a), MetaCommunity::ExtendedCommunity(b)) => a.cmp(b),
+            (MetaCommunity::LargeCommunity(a), MetaCommunity::LargeCommunity(b)) => a.cmp(b),
+            _ => Ordering::Equal,
+        })
+    }
+}
+
+impl MetaCommunity {
+    /// Rank of the community kind used for ordering, see [MetaCommunity].
+    fn kind_rank(&self) -> u8 {
+        match self {
+            MetaCommunity::Community(_) => 0,
+            MetaCommunity::ExtendedCommunity(ExtendedCommunity::Ipv6AddressSpecific(_)) => 2,
+            MetaCommunity::ExtendedCommunity(_) => 1,
+            MetaCommunity::LargeCommunity(_) => 3,
+        }
+    }
+
+    /// Length of the community in bytes when encoded in its attribute.
+    ///
+    /// Regular communities are 4 bytes, extended communities 8 bytes, IPv6 address specific
+    /// extended communities 20 bytes, and large communities 12 bytes.
+    pub fn len_bytes(&self) -> usize {
+        match self {
+            MetaCommunity::Community(_) => 4,
+            MetaCommunity::ExtendedCommunity(ExtendedCommunity::Ipv6AddressSpecific(_)) => 20,
+            MetaCommunity::ExtendedCommunity(_) => 8,
+            MetaCommunity::LargeCommunity(_) => 12,
+        }
+    }
+}
+
+/// Total encoded length of the communities in bytes, see [MetaCommunity::len_bytes].
+pub fn communities_byte_len(communities: &[MetaCommunity]) -> usize {
+    communities.iter().map(|c| c.len_bytes()).sum()
+}
+
+/// Sort communities into a deterministic order, e.g. before serializing.
+///
+/// See [MetaCommunity] for the ordering.
+pub fn sort_communities(communities: &mut [MetaCommunity]) {
+    communities.sort();
+}
+
+/// Check that a community survives a round-trip through its string form, i.e. that parsing its
+/// `Display` output with [FromStr] gives back the same community.
+pub fn community_roundtrip_ok(c: &MetaCommunity) -> bool {
+    MetaCommunity::from_str(c.to_string().as_str()).map_or(false, |parsed| parsed == *c)
+}
+
+/// Regular community.
+///
+/// The global administrator of a regular community is a 16-bit ASN. Use [Community::custom] to
+/// construct a custom community with a validated ASN; 32-bit ASNs should use [LargeCommunity]
+/// instead.
+#[derive(Debug, PartialEq, Copy, Clone, Eq, Hash, PartialOrd, Ord)]
 pub enum Community {
     NoExport,
     NoAdvertise,
@@ -19,7 +96,19 @@ pub enum Community {
     Custom(Asn, u16),
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+impl Community {
+    /// Create a custom community, rejecting ASNs that do not fit into 16 bits.
+    pub fn custom(asn: Asn, value: u16) -> Result<Community, BgpModelsError> {
+        if asn.asn > u16::MAX as u32 {
+            return Err(BgpModelsError::CommunityParsingError(
+                format!("ASN {} does not fit in a regular community, use a large community instead", asn)
+            ))
+        }
+        Ok(Community::Custom(asn, value))
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, PartialOrd, Ord)]
 pub struct LargeCommunity {
     pub global_administrator: u32,
     pub local_data: [u32; 2],
@@ -32,6 +121,38 @@ impl LargeCommunity {
             local_data,
         }
     }
+
+    pub fn global_administrator(&self) -> u32 {
+        self.global_administrator
+    }
+
+    pub fn local_data_part1(&self) -> u32 {
+        self.local_data[0]
+    }
+
+    pub fn local_data_part2(&self) -> u32 {
+        self.local_data[1]
+    }
+}
+
+/// Parse a large community from `global:local1:local2`, with an optional `lg:` prefix as
+/// produced by its `Display` implementation.
+impl FromStr for LargeCommunity {
+    type Err = BgpModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let values = s.strip_prefix("lg:").unwrap_or(s)
+            .split(':')
+            .map(|v| v.parse::<u32>())
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(|e| BgpModelsError::CommunityParsingError(format!("invalid large community {}: {}", s, e)))?;
+        if values.len() != 3 {
+            return Err(BgpModelsError::CommunityParsingError(
+                format!("large community must have three values: {}", s)
+            ))
+        }
+        Ok(LargeCommunity::new(values[0], [values[1], values[2]]))
+    }
 }
 
 /// Type definitions of extended communities
@@ -54,6 +175,38 @@ pub enum ExtendedCommunityType {
     // the rest are either draft or experimental
 }
 
+/// Look up the [ExtendedCommunityType] of the type high octet of an extended community.
+///
+/// The transitive bit (`0x40`) is part of the type, see [ExtendedCommunityType]. Returns `None`
+/// for types that are not modeled.
+pub fn extended_community_type(type_byte: u8) -> Option<ExtendedCommunityType> {
+    ExtendedCommunityType::from_u8(type_byte)
+}
+
+/// Type high octets of the extended types, i.e. types with a sub-type registry, as listed in the
+/// IANA registries of transitive and non-transitive extended community types
+/// ([RFC 7153 section 5](https://datatracker.ietf.org/doc/html/rfc7153#section-5)).
+const EXTENDED_TYPES: &[u8] = &[
+    // transitive two-octet AS, IPv4 address, four-octet AS and opaque
+    0x00, 0x01, 0x02, 0x03,
+    // EVPN (RFC 7432)
+    0x06,
+    // generic transitive experimental use, parts 1 to 3 (RFC 7153, RFC 8955)
+    0x80, 0x81, 0x82,
+    // non-transitive two-octet AS, IPv4 address, four-octet AS and opaque
+    0x40, 0x41, 0x42, 0x43,
+];
+
+/// Check whether the type high octet is of an extended type, i.e. whether the second octet is the
+/// type low (sub-type) rather than the start of the value.
+///
+/// RFC 4360 has no flag bit for this: whether a type is regular or extended is part of its IANA
+/// registration. Unassigned types and types registered as regular types, e.g. `0x04` (QoS
+/// marking), are reported as regular.
+pub fn is_extended_type_high(type_byte: u8) -> bool {
+    EXTENDED_TYPES.contains(&type_byte)
+}
+
 /// Extended Communities.
 ///
 /// It is a 8-octet data that has flexible definition based on the types:
@@ -79,7 +232,7 @@ pub enum ExtendedCommunityType {
 ///       (*) Present for Extended types only, used for the Value field
 ///           otherwise.
 /// ```
-#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, PartialOrd, Ord)]
 pub enum ExtendedCommunity {
     TransitiveTwoOctetAsSpecific(TwoOctetAsSpecific),
     TransitiveIpv4AddressSpecific(Ipv4AddressSpecific),
@@ -93,7 +246,73 @@ pub enum ExtendedCommunity {
     Raw([u8; 8]),
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+impl ExtendedCommunity {
+    /// Get the (type, subtype) pair of the community. Returns `None` for [ExtendedCommunity::Raw].
+    fn type_subtype(&self) -> Option<(u8, u8)> {
+        match self {
+            ExtendedCommunity::TransitiveTwoOctetAsSpecific(ec) |
+            ExtendedCommunity::NonTransitiveTwoOctetAsSpecific(ec) => Some((ec.ec_type, ec.ec_subtype)),
+            ExtendedCommunity::TransitiveIpv4AddressSpecific(ec) |
+            ExtendedCommunity::NonTransitiveIpv4AddressSpecific(ec) => Some((ec.ec_type, ec.ec_subtype)),
+            ExtendedCommunity::TransitiveFourOctetAsSpecific(ec) |
+            ExtendedCommunity::NonTransitiveFourOctetAsSpecific(ec) => Some((ec.ec_type, ec.ec_subtype)),
+            ExtendedCommunity::TransitiveOpaque(ec) |
+            ExtendedCommunity::NonTransitiveOpaque(ec) => Some((ec.ec_type, ec.ec_subtype)),
+            ExtendedCommunity::Ipv6AddressSpecific(ec) => Some((ec.ec_type, ec.ec_subtype)),
+            ExtendedCommunity::Raw(_) => None,
+        }
+    }
+
+    /// Get the name of commonly used subtypes, such as `route-target` and `route-origin`.
+    ///
+    /// The names follow the IANA registries of extended community subtypes:
+    /// <https://www.iana.org/assignments/bgp-extended-communities/bgp-extended-communities.xhtml>
+    pub fn subtype_name(&self) -> Option<&'static str> {
+        if let ExtendedCommunity::Ipv6AddressSpecific(ec) = self {
+            return ec.subtype_name()
+        }
+        let (ec_type, ec_subtype) = self.type_subtype()?;
+        match (ec_type, ec_subtype) {
+            (0x00, 0x02) | (0x01, 0x02) | (0x02, 0x02) => Some("route-target"),
+            (0x00, 0x03) | (0x01, 0x03) | (0x02, 0x03) => Some("route-origin"),
+            (0x00, 0x05) | (0x01, 0x05) | (0x02, 0x05) => Some("ospf-domain-id"),
+            (0x00, 0x08) | (0x02, 0x08) => Some("bgp-data-collection"),
+            (0x00, 0x09) | (0x02, 0x09) => Some("source-as"),
+            (0x01, 0x0b) => Some("vrf-route-import"),
+            (0x03, 0x0b) => Some("color"),
+            (0x03, 0x0c) => Some("encapsulation"),
+            (0x40, 0x04) => Some("link-bandwidth"),
+            (0x43, 0x00) => Some("origin-validation-state"),
+            _ => None,
+        }
+    }
+
+    /// Check whether the community is a route target.
+    pub fn is_route_target(&self) -> bool {
+        self.subtype_name() == Some("route-target")
+    }
+}
+
+/// IPv6 Address Specific Extended Community
+///
+/// <https://datatracker.ietf.org/doc/html/rfc5701#section-2>
+///
+/// ```text
+///    0                   1                   2                   3
+///    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+///   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///   | 0x00 or 0x40  |    Sub-Type   |    Global Administrator       |
+///   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///   |          Global Administrator (cont.)                         |
+///   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///   |          Global Administrator (cont.)                         |
+///   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///   |          Global Administrator (cont.)                         |
+///   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///   | Global Administrator (cont.)  |    Local Administrator        |
+///   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, PartialOrd, Ord)]
 pub struct Ipv6AddressSpecific {
     pub ec_type: u8,
     pub ec_subtype: u8,
@@ -103,11 +322,67 @@ pub struct Ipv6AddressSpecific {
     pub local_administrator: [u8; 2]
 }
 
+impl Ipv6AddressSpecific {
+    /// Create a transitive IPv6 address specific route target (type 0x00, subtype 0x02).
+    pub fn route_target(ip: Ipv6Addr, value: u16) -> Ipv6AddressSpecific {
+        Ipv6AddressSpecific {
+            ec_type: 0x00,
+            ec_subtype: 0x02,
+            global_administrator: ip,
+            local_administrator: value.to_be_bytes(),
+        }
+    }
+
+    /// Get the name of the subtype defined in [RFC 5701](https://datatracker.ietf.org/doc/html/rfc5701).
+    pub fn subtype_name(&self) -> Option<&'static str> {
+        match (self.ec_type, self.ec_subtype) {
+            (0x00, 0x02) => Some("route-target"),
+            (0x00, 0x03) => Some("route-origin"),
+            _ => None,
+        }
+    }
+
+    /// Check whether the community is a route target.
+    pub fn is_route_target(&self) -> bool {
+        self.subtype_name() == Some("route-target")
+    }
+
+    /// Encode the community into its 20-octet wire format.
+    pub fn to_bytes(&self) -> [u8; 20] {
+        let mut bytes = [0u8; 20];
+        bytes[0] = self.ec_type;
+        bytes[1] = self.ec_subtype;
+        bytes[2..18].copy_from_slice(&self.global_administrator.octets());
+        bytes[18..20].copy_from_slice(&self.local_administrator);
+        bytes
+    }
+}
+
+impl TryFrom<&[u8]> for Ipv6AddressSpecific {
+    type Error = BgpModelsError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() != 20 {
+            return Err(BgpModelsError::CommunityParsingError(
+                format!("IPv6 address specific extended community must be 20 bytes, got {}", bytes.len())
+            ))
+        }
+        let mut addr = [0u8; 16];
+        addr.copy_from_slice(&bytes[2..18]);
+        Ok(Ipv6AddressSpecific {
+            ec_type: bytes[0],
+            ec_subtype: bytes[1],
+            global_administrator: Ipv6Addr::from(addr),
+            local_administrator: [bytes[18], bytes[19]],
+        })
+    }
+}
+
 
 /// Two-Octet AS Specific Extended Community
 ///
 /// <https://datatracker.ietf.org/doc/html/rfc4360#section-3.1>
-#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, PartialOrd, Ord)]
 pub struct TwoOctetAsSpecific {
     pub ec_type: u8,
     pub ec_subtype: u8,
@@ -120,7 +395,7 @@ pub struct TwoOctetAsSpecific {
 /// Four-Octet AS Specific Extended Community
 ///
 /// <https://datatracker.ietf.org/doc/html/rfc5668#section-2>
-#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, PartialOrd, Ord)]
 pub struct FourOctetAsSpecific {
     pub ec_type: u8,
     pub ec_subtype: u8,
@@ -133,7 +408,7 @@ pub struct FourOctetAsSpecific {
 /// IPv4 Address Specific Extended Community
 ///
 /// <https://datatracker.ietf.org/doc/html/rfc4360#section-3.2>
-#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, PartialOrd, Ord)]
 pub struct Ipv4AddressSpecific {
     pub ec_type: u8,
     pub ec_subtype: u8,
@@ -146,7 +421,7 @@ pub struct Ipv4AddressSpecific {
 /// Opaque Extended Community
 ///
 /// <https://datatracker.ietf.org/doc/html/rfc4360#section-3.3>
-#[derive(Debug, PartialEq, Clone, Copy, Eq)]
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, PartialOrd, Ord)]
 pub struct Opaque {
     pub ec_type: u8,
     pub ec_subtype: u8,
@@ -154,6 +429,187 @@ pub struct Opaque {
     pub value: [u8; 6],
 }
 
+impl Opaque {
+    /// Decode the community as a [ColorExtendedCommunity].
+    ///
+    /// Returns `None` if the community is not a transitive opaque community of the color
+    /// subtype (type `0x03`, subtype `0x0b`).
+    pub fn as_color_extended(&self) -> Option<ColorExtendedCommunity> {
+        if self.ec_type != 0x03 || self.ec_subtype != 0x0b {
+            return None
+        }
+        Some(ColorExtendedCommunity {
+            flags: self.value[0],
+            color: u32::from_be_bytes([self.value[2], self.value[3], self.value[4], self.value[5]]),
+        })
+    }
+}
+
+/// Color Extended Community
+///
+/// <https://datatracker.ietf.org/doc/html/rfc9012#section-4.3>
+///
+/// ```text
+///    0                   1                   2                   3
+///    0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+///   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///   | Type (0x03)   | Sub-Type(0x0b)|    Flags                      |
+///   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+///   |                          Color Value                          |
+///   +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
+/// ```
+///
+/// `flags` holds the first octet of the 2-octet flags field, which carries the color-only (CO)
+/// bits defined in [RFC 9256](https://datatracker.ietf.org/doc/html/rfc9256#section-8.8.1).
+/// The second octet is reserved.
+#[derive(Debug, PartialEq, Clone, Copy, Eq, Hash, PartialOrd, Ord)]
+pub struct ColorExtendedCommunity {
+    pub flags: u8,
+    pub color: u32,
+}
+
+/////////////
+// FROMSTR //
+/////////////
+
+fn string_to_bytes(s: &str) -> Option<Vec<u8>> {
+    // an odd trailing character yields `None` from `get`
+    (0..s.len()).step_by(2)
+        .map(|i| s.get(i..i + 2).and_then(|b| u8::from_str_radix(b, 16).ok()))
+        .collect()
+}
+
+fn parse_hex<T: for<'a> TryFrom<&'a [u8]>>(s: &str, orig: &str) -> Result<T, BgpModelsError> {
+    string_to_bytes(s)
+        .and_then(|v| T::try_from(v.as_slice()).ok())
+        .ok_or_else(|| BgpModelsError::CommunityParsingError(format!("invalid hex value {} in {}", s, orig)))
+}
+
+fn parse_num<T: FromStr>(s: &str, orig: &str) -> Result<T, BgpModelsError> {
+    s.parse::<T>().map_err(|_| BgpModelsError::CommunityParsingError(format!("invalid value {} in {}", s, orig)))
+}
+
+/// Parse a community from `asn:value` or one of the well-known names (e.g. `no-export`).
+impl FromStr for Community {
+    type Err = BgpModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "no-export" => Ok(Community::NoExport),
+            "no-advertise" => Ok(Community::NoAdvertise),
+            "no-export-sub-confed" => Ok(Community::NoExportSubConfed),
+            _ => {
+                let mut parts = s.split(':');
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(asn), Some(value), None) => {
+                        Community::custom(parse_num::<u32>(asn, s)?.into(), parse_num::<u16>(value, s)?)
+                    }
+                    _ => Err(BgpModelsError::CommunityParsingError(format!("invalid community {}", s)))
+                }
+            }
+        }
+    }
+}
+
+/// Parse an extended community from the format produced by its `Display` implementation,
+/// e.g. `ecas2:0:2:65000:00000001`.
+///
+/// The transitive and non-transitive variants are told apart by the transitive bit of the type.
+impl FromStr for ExtendedCommunity {
+    type Err = BgpModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || BgpModelsError::CommunityParsingError(format!("invalid extended community {}", s));
+        let mut kind_rest = s.splitn(2, ':');
+        let kind = kind_rest.next().ok_or_else(invalid)?;
+        let rest = kind_rest.next().ok_or_else(invalid)?;
+        if kind == "ecraw" {
+            return Ok(ExtendedCommunity::Raw(parse_hex::<[u8; 8]>(rest, s)?))
+        }
+
+        let mut parts = rest.splitn(3, ':');
+        let ec_type = parse_num::<u8>(parts.next().ok_or_else(invalid)?, s)?;
+        let ec_subtype = parse_num::<u8>(parts.next().ok_or_else(invalid)?, s)?;
+        let value = parts.next().ok_or_else(invalid)?;
+        let transitive = ec_type & 0x40 == 0;
+
+        if kind == "ecop" {
+            let ec = Opaque { ec_type, ec_subtype, value: parse_hex::<[u8; 6]>(value, s)? };
+            return Ok(match transitive {
+                true => ExtendedCommunity::TransitiveOpaque(ec),
+                false => ExtendedCommunity::NonTransitiveOpaque(ec),
+            })
+        }
+
+        // the global administrator may contain colons (IPv6), the local administrator never does
+        let mut local_global = value.rsplitn(2, ':');
+        let local = local_global.next().ok_or_else(invalid)?;
+        let global = local_global.next().ok_or_else(invalid)?;
+        match kind {
+            "ecas2" => {
+                let ec = TwoOctetAsSpecific {
+                    ec_type,
+                    ec_subtype,
+                    global_administrator: parse_num::<u32>(global, s)?.into(),
+                    local_administrator: parse_hex::<[u8; 4]>(local, s)?,
+                };
+                Ok(match transitive {
+                    true => ExtendedCommunity::TransitiveTwoOctetAsSpecific(ec),
+                    false => ExtendedCommunity::NonTransitiveTwoOctetAsSpecific(ec),
+                })
+            }
+            "ecv4" => {
+                let ec = Ipv4AddressSpecific {
+                    ec_type,
+                    ec_subtype,
+                    global_administrator: parse_num::<Ipv4Addr>(global, s)?,
+                    local_administrator: parse_hex::<[u8; 2]>(local, s)?,
+                };
+                Ok(match transitive {
+                    true => ExtendedCommunity::TransitiveIpv4AddressSpecific(ec),
+                    false => ExtendedCommunity::NonTransitiveIpv4AddressSpecific(ec),
+                })
+            }
+            "ecas4" => {
+                let ec = FourOctetAsSpecific {
+                    ec_type,
+                    ec_subtype,
+                    global_administrator: parse_num::<u32>(global, s)?.into(),
+                    local_administrator: parse_hex::<[u8; 2]>(local, s)?,
+                };
+                Ok(match transitive {
+                    true => ExtendedCommunity::TransitiveFourOctetAsSpecific(ec),
+                    false => ExtendedCommunity::NonTransitiveFourOctetAsSpecific(ec),
+                })
+            }
+            "ecv6" => {
+                Ok(ExtendedCommunity::Ipv6AddressSpecific(Ipv6AddressSpecific {
+                    ec_type,
+                    ec_subtype,
+                    global_administrator: parse_num::<Ipv6Addr>(global, s)?,
+                    local_administrator: parse_hex::<[u8; 2]>(local, s)?,
+                }))
+            }
+            _ => Err(invalid())
+        }
+    }
+}
+
+/// Parse any kind of community from the format produced by its `Display` implementation.
+impl FromStr for MetaCommunity {
+    type Err = BgpModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("lg:") {
+            Ok(MetaCommunity::LargeCommunity(LargeCommunity::from_str(s)?))
+        } else if s.starts_with("ec") {
+            Ok(MetaCommunity::ExtendedCommunity(ExtendedCommunity::from_str(s)?))
+        } else {
+            Ok(MetaCommunity::Community(Community::from_str(s)?))
+        }
+    }
+}
+
 /////////////
 // DISPLAY //
 /////////////
@@ -247,3 +703,304 @@ impl_serialize!(Community);
 impl_serialize!(ExtendedCommunity);
 impl_serialize!(LargeCommunity);
 impl_serialize!(MetaCommunity);
+
+/// Wrapper of [MetaCommunity] that serializes with the kind of community tagged, e.g.
+/// `{"type": "large", "value": "65000:1:2"}`.
+///
+/// The `type` field is one of `regular`, `extended`, or `large`. [MetaCommunity] itself keeps
+/// serializing as a flat string.
+#[derive(Debug, PartialEq, Copy, Clone, Eq)]
+pub struct TaggedCommunity(pub MetaCommunity);
+
+impl From<MetaCommunity> for TaggedCommunity {
+    fn from(c: MetaCommunity) -> Self {
+        TaggedCommunity(c)
+    }
+}
+
+impl Serialize for TaggedCommunity {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: serde::Serializer {
+        let (community_type, value) = match &self.0 {
+            MetaCommunity::Community(c) => ("regular", c.to_string()),
+            MetaCommunity::ExtendedCommunity(c) => ("extended", c.to_string()),
+            MetaCommunity::LargeCommunity(c) => (
+                "large",
+                format!("{}:{}:{}", c.global_administrator, c.local_data[0], c.local_data[1])
+            ),
+        };
+        let mut state = serializer.serialize_struct("TaggedCommunity", 2)?;
+        state.serialize_field("type", community_type)?;
+        state.serialize_field("value", &value)?;
+        state.end()
+    }
+}
+
+/////////////////
+// DESERIALIZE //
+/////////////////
+
+macro_rules! impl_deserialize {
+    ($a:ident) => {
+        impl<'de> Deserialize<'de> for $a {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: serde::Deserializer<'de> {
+                let s = String::deserialize(deserializer)?;
+                $a::from_str(s.as_str()).map_err(serde::de::Error::custom)
+            }
+        }
+    }
+}
+
+impl_deserialize!(Community);
+impl_deserialize!(ExtendedCommunity);
+impl_deserialize!(LargeCommunity);
+impl_deserialize!(MetaCommunity);
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use super::*;
+
+    #[test]
+    fn test_ipv6_address_specific_bytes() {
+        let ip = Ipv6Addr::from_str("2001:db8::1").unwrap();
+        let ec = Ipv6AddressSpecific::route_target(ip, 100);
+        assert_eq!(ec.ec_type, 0x00);
+        assert_eq!(ec.ec_subtype, 0x02);
+        assert_eq!(ec.local_administrator, [0, 100]);
+
+        let bytes = ec.to_bytes();
+        assert_eq!(bytes[0..2], [0x00, 0x02]);
+        assert_eq!(bytes[2..18], ip.octets());
+        assert_eq!(bytes[18..20], [0, 100]);
+        assert_eq!(Ipv6AddressSpecific::try_from(&bytes[..]).unwrap(), ec);
+
+        assert!(Ipv6AddressSpecific::try_from(&bytes[..8]).is_err());
+    }
+
+    #[test]
+    fn test_ipv6_route_target() {
+        let ec = Ipv6AddressSpecific::route_target(Ipv6Addr::from_str("2001:db8::1").unwrap(), 100);
+        assert_eq!(ec.subtype_name(), Some("route-target"));
+        assert!(ec.is_route_target());
+
+        let community = ExtendedCommunity::Ipv6AddressSpecific(ec);
+        assert!(community.is_route_target());
+        assert_eq!(community.to_string(), "ecv6:0:2:2001:db8::1:0064");
+
+        let origin = Ipv6AddressSpecific { ec_subtype: 0x03, ..ec };
+        assert_eq!(origin.subtype_name(), Some("route-origin"));
+        assert!(!origin.is_route_target());
+    }
+
+    #[test]
+    fn test_extended_community_subtype_name() {
+        let rt = ExtendedCommunity::TransitiveTwoOctetAsSpecific(TwoOctetAsSpecific {
+            ec_type: 0x00,
+            ec_subtype: 0x02,
+            global_administrator: 65000.into(),
+            local_administrator: [0, 0, 0, 1],
+        });
+        assert_eq!(rt.subtype_name(), Some("route-target"));
+        assert!(rt.is_route_target());
+
+        let ro = ExtendedCommunity::TransitiveIpv4AddressSpecific(Ipv4AddressSpecific {
+            ec_type: 0x01,
+            ec_subtype: 0x03,
+            global_administrator: Ipv4Addr::new(10, 0, 0, 1),
+            local_administrator: [0, 1],
+        });
+        assert_eq!(ro.subtype_name(), Some("route-origin"));
+        assert!(!ro.is_route_target());
+
+        // route targets are transitive only
+        let non_transitive = ExtendedCommunity::NonTransitiveTwoOctetAsSpecific(TwoOctetAsSpecific {
+            ec_type: 0x40,
+            ec_subtype: 0x02,
+            global_administrator: 65000.into(),
+            local_administrator: [0, 0, 0, 1],
+        });
+        assert_eq!(non_transitive.subtype_name(), None);
+        assert!(!non_transitive.is_route_target());
+
+        let ovs = ExtendedCommunity::NonTransitiveOpaque(Opaque {
+            ec_type: 0x43,
+            ec_subtype: 0x00,
+            value: [0, 0, 0, 0, 0, 0],
+        });
+        assert_eq!(ovs.subtype_name(), Some("origin-validation-state"));
+
+        let v6 = ExtendedCommunity::Ipv6AddressSpecific(
+            Ipv6AddressSpecific::route_target(Ipv6Addr::from_str("2001:db8::1").unwrap(), 1)
+        );
+        assert!(v6.is_route_target());
+        assert_eq!(ExtendedCommunity::Raw([0; 8]).subtype_name(), None);
+    }
+
+    #[test]
+    fn test_large_community_from_str() {
+        assert_eq!(LargeCommunity::from_str("65000:1:2").unwrap(), LargeCommunity::new(65000, [1, 2]));
+        let lc = LargeCommunity::new(4200000000, [0, 4294967295]);
+        assert_eq!(LargeCommunity::from_str(lc.to_string().as_str()).unwrap(), lc);
+
+        assert!(LargeCommunity::from_str("65000:1").is_err());
+        assert!(LargeCommunity::from_str("65000:1:2:3").is_err());
+        assert!(LargeCommunity::from_str("65000:1:4294967296").is_err());
+    }
+
+    fn communities_of_each_kind() -> Vec<MetaCommunity> {
+        vec![
+            MetaCommunity::Community(Community::NoExport),
+            MetaCommunity::Community(Community::NoAdvertise),
+            MetaCommunity::Community(Community::NoExportSubConfed),
+            MetaCommunity::Community(Community::Custom(65000.into(), 100)),
+            MetaCommunity::LargeCommunity(LargeCommunity::new(65000, [1, 2])),
+            MetaCommunity::ExtendedCommunity(ExtendedCommunity::TransitiveTwoOctetAsSpecific(TwoOctetAsSpecific {
+                ec_type: 0x00, ec_subtype: 0x02, global_administrator: 65000.into(), local_administrator: [0, 0, 0, 1],
+            })),
+            MetaCommunity::ExtendedCommunity(ExtendedCommunity::NonTransitiveIpv4AddressSpecific(Ipv4AddressSpecific {
+                ec_type: 0x41, ec_subtype: 0x02, global_administrator: Ipv4Addr::new(10, 0, 0, 1), local_administrator: [0, 1],
+            })),
+            MetaCommunity::ExtendedCommunity(ExtendedCommunity::TransitiveFourOctetAsSpecific(FourOctetAsSpecific {
+                ec_type: 0x02, ec_subtype: 0x02, global_administrator: 4200000000u32.into(), local_administrator: [0xAB, 0xCD],
+            })),
+            MetaCommunity::ExtendedCommunity(ExtendedCommunity::NonTransitiveOpaque(Opaque {
+                ec_type: 0x43, ec_subtype: 0x00, value: [0, 0, 0, 0, 0, 2],
+            })),
+            MetaCommunity::ExtendedCommunity(ExtendedCommunity::Ipv6AddressSpecific(
+                Ipv6AddressSpecific::route_target(Ipv6Addr::from_str("2001:db8::1").unwrap(), 100)
+            )),
+            MetaCommunity::ExtendedCommunity(ExtendedCommunity::Raw([1, 2, 3, 4, 5, 6, 7, 8])),
+        ]
+    }
+
+    #[test]
+    fn test_community_from_str() {
+        for c in communities_of_each_kind() {
+            assert_eq!(MetaCommunity::from_str(c.to_string().as_str()).unwrap(), c);
+        }
+
+        assert!(Community::from_str("65000").is_err());
+        assert!(Community::from_str("65000:65536").is_err());
+        assert!(ExtendedCommunity::from_str("ecop:3:11:0000").is_err());
+        assert!(ExtendedCommunity::from_str("ecfoo:0:2:1:00000001").is_err());
+    }
+
+    #[test]
+    fn test_community_roundtrip_ok() {
+        for c in communities_of_each_kind() {
+            assert!(community_roundtrip_ok(&c), "{} does not round-trip", c);
+        }
+    }
+
+    #[test]
+    fn test_extended_community_type_byte() {
+        assert_eq!(extended_community_type(0x00), Some(ExtendedCommunityType::TransitiveTwoOctetAsSpecific));
+        assert_eq!(extended_community_type(0x43), Some(ExtendedCommunityType::NonTransitiveOpaque));
+        assert!(is_extended_type_high(0x02));
+        assert!(is_extended_type_high(0x41));
+
+        // not a modeled type, the second octet is part of the value
+        assert_eq!(extended_community_type(0x30), None);
+        assert!(!is_extended_type_high(0x30));
+        assert!(!is_extended_type_high(0x04));
+        assert!(is_extended_type_high(0x06));
+        for type_byte in 0x80..=0x82 {
+            assert!(is_extended_type_high(type_byte));
+        }
+    }
+
+    #[test]
+    fn test_custom_community() {
+        assert_eq!(Community::custom(65535.into(), 1).unwrap(), Community::Custom(65535.into(), 1));
+        assert!(Community::custom(65536.into(), 1).is_err());
+        assert!(Community::from_str("4200000000:1").is_err());
+    }
+
+    #[test]
+    fn test_color_extended_community() {
+        let color = Opaque { ec_type: 0x03, ec_subtype: 0x0b, value: [0x40, 0, 0, 0, 0, 100] };
+        assert_eq!(color.as_color_extended(), Some(ColorExtendedCommunity { flags: 0x40, color: 100 }));
+
+        let encap = Opaque { ec_type: 0x03, ec_subtype: 0x0c, value: [0, 0, 0, 0, 0, 8] };
+        assert_eq!(encap.as_color_extended(), None);
+    }
+
+    #[test]
+    fn test_tagged_community() {
+        let c = MetaCommunity::LargeCommunity(LargeCommunity::new(65000, [1, 2]));
+        assert_eq!(serde_json::to_value(c).unwrap(), serde_json::json!("lg:65000:1:2"));
+        assert_eq!(
+            serde_json::to_value(TaggedCommunity(c)).unwrap(),
+            serde_json::json!({"type": "large", "value": "65000:1:2"})
+        );
+
+        let c = MetaCommunity::Community(Community::NoExport);
+        assert_eq!(serde_json::to_value(c).unwrap(), serde_json::json!("no-export"));
+        assert_eq!(
+            serde_json::to_value(TaggedCommunity::from(c)).unwrap(),
+            serde_json::json!({"type": "regular", "value": "no-export"})
+        );
+    }
+
+    #[test]
+    fn test_large_community_accessors() {
+        let c = LargeCommunity::new(4200000000, [1, 2]);
+        assert_eq!(c.global_administrator(), 4200000000);
+        assert_eq!(c.local_data_part1(), 1);
+        assert_eq!(c.local_data_part2(), 2);
+    }
+
+    #[test]
+    fn test_communities_byte_len() {
+        let communities = [
+            MetaCommunity::Community(Community::NoExport),
+            MetaCommunity::ExtendedCommunity(ExtendedCommunity::from_str("ecas2:0:2:65000:00000064").unwrap()),
+            MetaCommunity::ExtendedCommunity(ExtendedCommunity::Ipv6AddressSpecific(
+                Ipv6AddressSpecific::route_target(Ipv6Addr::from_str("2001:db8::1").unwrap(), 1)
+            )),
+            MetaCommunity::LargeCommunity(LargeCommunity::new(65000, [1, 2])),
+        ];
+        assert_eq!(communities.iter().map(|c| c.len_bytes()).collect::<Vec<usize>>(), vec![4, 8, 20, 12]);
+        assert_eq!(communities_byte_len(&communities), 44);
+        assert_eq!(communities_byte_len(&[]), 0);
+    }
+
+    #[test]
+    fn test_sort_communities() {
+        let large = MetaCommunity::LargeCommunity(LargeCommunity::new(65000, [1, 2]));
+        let ipv6 = MetaCommunity::ExtendedCommunity(ExtendedCommunity::Ipv6AddressSpecific(
+            Ipv6AddressSpecific::route_target(Ipv6Addr::from_str("2001:db8::1").unwrap(), 1)
+        ));
+        let ext = MetaCommunity::ExtendedCommunity(ExtendedCommunity::from_str("ecas2:0:2:65000:00000064").unwrap());
+        let regular_1 = MetaCommunity::Community(Community::Custom(3356.into(), 100));
+        let regular_2 = MetaCommunity::Community(Community::Custom(65000.into(), 1));
+        let no_export = MetaCommunity::Community(Community::NoExport);
+
+        let raw = MetaCommunity::ExtendedCommunity(ExtendedCommunity::Raw([0x04, 0, 0, 0, 0, 0, 0, 1]));
+
+        let mut communities = vec![large, ipv6, raw, regular_2, ext, no_export, regular_1, large];
+        sort_communities(&mut communities);
+        assert_eq!(communities, vec![no_export, regular_1, regular_2, ext, raw, ipv6, large, large]);
+    }
+
+    #[test]
+    fn test_community_btreemap() {
+        let mut counts: BTreeMap<Community, usize> = BTreeMap::new();
+        for c in [
+            Community::Custom(65000.into(), 2),
+            Community::NoExport,
+            Community::Custom(65000.into(), 1),
+            Community::Custom(3356.into(), 100),
+            Community::NoExport,
+        ] {
+            *counts.entry(c).or_insert(0) += 1;
+        }
+        let ordered: Vec<(Community, usize)> = counts.into_iter().collect();
+        assert_eq!(ordered, vec![
+            (Community::NoExport, 2),
+            (Community::Custom(3356.into(), 100), 1),
+            (Community::Custom(65000.into(), 1), 1),
+            (Community::Custom(65000.into(), 2), 1),
+        ]);
+    }
+}
@@ -1,29 +1,172 @@
 use std::cmp::Ordering;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
-use std::net::IpAddr;
+use std::hash::{Hash, Hasher};
+use std::convert::TryFrom;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
+use ipnetwork::IpNetwork;
 use itertools::Itertools;
-use crate::bgp::attributes::{AsPath, AtomicAggregate, Origin};
+use num_traits::FromPrimitive;
+use crate::bgp::attributes::{resolve_next_hop, AsPath, AsPathSegment, AtomicAggregate, Attribute, AttributeValue, Origin};
 use crate::bgp::community::*;
-use crate::network::{Asn, NetworkPrefix};
-use serde::{Serialize, Serializer};
+use crate::err::BgpModelsError;
+use crate::network::{is_reserved_asn, Asn, NetworkPrefix};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 /// Element type.
 ///
 /// - ANNOUNCE: announcement/reachable prefix
 /// - WITHDRAW: withdrawn/unreachable prefix
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ElemType {
     ANNOUNCE,
     WITHDRAW,
 }
 
+impl ElemType {
+    /// Numeric code of the element type for compact binary storage.
+    ///
+    /// - `0`: ANNOUNCE
+    /// - `1`: WITHDRAW
+    pub fn code(&self) -> u8 {
+        match self {
+            ElemType::ANNOUNCE => 0,
+            ElemType::WITHDRAW => 1,
+        }
+    }
+
+    /// Convert a numeric code back into [ElemType]. Returns `None` for unknown codes.
+    pub fn from_code(code: u8) -> Option<ElemType> {
+        match code {
+            0 => Some(ElemType::ANNOUNCE),
+            1 => Some(ElemType::WITHDRAW),
+            _ => None,
+        }
+    }
+}
+
 impl Serialize for ElemType {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
-        Ok(serializer.serialize_str(match self {
+        serializer.serialize_str(match self {
             ElemType::ANNOUNCE => {"announce"}
             ElemType::WITHDRAW => {"withdraw"}
-        })?)
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for ElemType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let s = String::deserialize(deserializer)?;
+        match s.as_str() {
+            "announce" => Ok(ElemType::ANNOUNCE),
+            "withdraw" => Ok(ElemType::WITHDRAW),
+            _ => Err(de::Error::custom(format!("unknown element type {}", s))),
+        }
+    }
+}
+
+/// RPKI route origin validation state.
+///
+/// The numeric codes are the validation state values of the BGP Prefix Origin Validation State
+/// Extended Community ([RFC 8097](https://datatracker.ietf.org/doc/html/rfc8097#section-2)).
+#[derive(Debug, Primitive, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpkiValidationState {
+    Valid = 0,
+    NotFound = 1,
+    Invalid = 2,
+}
+
+/// ASPA AS path validation state.
+///
+/// <https://datatracker.ietf.org/doc/draft-ietf-sidrops-aspa-verification/>
+#[derive(Debug, Primitive, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AspaValidationState {
+    Valid = 0,
+    Invalid = 1,
+    Unknown = 2,
+}
+
+/// Origin ASNs of a route.
+///
+/// Most routes have a single origin, which is stored without allocating. Routes whose AS path
+/// ends with an AS_SET have multiple origins. Constructing from a `Vec` with [From] picks the
+/// variant by the number of ASNs. Comparison and hashing are by the list of ASNs, and it
+/// serializes as an array of ASNs.
+#[derive(Debug, Clone, Eq)]
+pub enum OriginAsns {
+    Single(Asn),
+    Multiple(Vec<Asn>),
+}
+
+impl OriginAsns {
+    pub fn as_slice(&self) -> &[Asn] {
+        match self {
+            OriginAsns::Single(asn) => std::slice::from_ref(asn),
+            OriginAsns::Multiple(asns) => asns.as_slice(),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Asn> {
+        self.as_slice().iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+
+    pub fn contains(&self, asn: &Asn) -> bool {
+        self.as_slice().contains(asn)
+    }
+
+    pub fn to_vec(&self) -> Vec<Asn> {
+        self.as_slice().to_vec()
+    }
+}
+
+impl From<Asn> for OriginAsns {
+    fn from(asn: Asn) -> Self {
+        OriginAsns::Single(asn)
+    }
+}
+
+impl From<Vec<Asn>> for OriginAsns {
+    fn from(asns: Vec<Asn>) -> Self {
+        match asns.as_slice() {
+            [asn] => OriginAsns::Single(*asn),
+            _ => OriginAsns::Multiple(asns),
+        }
+    }
+}
+
+impl PartialEq for OriginAsns {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+
+impl Hash for OriginAsns {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+
+impl Serialize for OriginAsns {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        self.as_slice().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for OriginAsns {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        Ok(Vec::<Asn>::deserialize(deserializer)?.into())
     }
 }
 
@@ -33,7 +176,11 @@ impl Serialize for ElemType {
 ///
 /// Note: it consumes more memory to construct BGP elements due to duplicate information
 /// shared between multiple elements of one MRT record.
-#[derive(Debug, Clone, Serialize, PartialEq)]
+///
+/// `origin_validation` and `aspa_validation` are not part of the BGP message. They are left
+/// `None` when parsing and can be filled in by enrichment pipelines using
+/// [BgpElem::set_origin_validation] and [BgpElem::set_aspa_validation].
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct BgpElem {
     pub timestamp: f64,
     #[serde(rename="type")]
@@ -43,14 +190,20 @@ pub struct BgpElem {
     pub prefix: NetworkPrefix,
     pub next_hop: Option<IpAddr>,
     pub as_path: Option<AsPath>,
-    pub origin_asns: Option<Vec<Asn>>,
+    pub origin_asns: Option<OriginAsns>,
     pub origin: Option<Origin>,
+    /// LOCAL_PREF value. `Some(0)` is an explicit zero, `None` means the attribute is absent.
     pub local_pref: Option<u32>,
+    /// MULTI_EXIT_DISC value. `Some(0)` is an explicit zero, `None` means the attribute is absent.
     pub med: Option<u32>,
     pub communities: Option<Vec<MetaCommunity>>,
     pub atomic: Option<AtomicAggregate>,
     pub aggr_asn: Option<Asn>,
     pub aggr_ip: Option<IpAddr>,
+    pub cluster_list: Option<Vec<IpAddr>>,
+    pub originator_id: Option<IpAddr>,
+    pub origin_validation: Option<RpkiValidationState>,
+    pub aspa_validation: Option<AspaValidationState>,
 }
 
 impl Eq for BgpElem {}
@@ -77,7 +230,7 @@ pub struct BgpElemRef<'a> {
     pub prefix: &'a NetworkPrefix,
     pub next_hop: &'a Option<IpAddr>,
     pub as_path: &'a Option<AsPath>,
-    pub origin_asns: &'a Option<Vec<Asn>>,
+    pub origin_asns: &'a Option<OriginAsns>,
     pub origin: &'a Option<Origin>,
     pub local_pref: &'a Option<u32>,
     pub med: &'a Option<u32>,
@@ -85,6 +238,10 @@ pub struct BgpElemRef<'a> {
     pub atomic: &'a Option<AtomicAggregate>,
     pub aggr_asn: &'a Option<Asn>,
     pub aggr_ip: &'a Option<IpAddr>,
+    pub cluster_list: &'a Option<Vec<IpAddr>>,
+    pub originator_id: &'a Option<IpAddr>,
+    pub origin_validation: &'a Option<RpkiValidationState>,
+    pub aspa_validation: &'a Option<AspaValidationState>,
 }
 
 impl Default for BgpElem {
@@ -104,11 +261,877 @@ impl Default for BgpElem {
             communities: None,
             atomic: None,
             aggr_asn: None,
-            aggr_ip: None
+            aggr_ip: None,
+            cluster_list: None,
+            originator_id: None,
+            origin_validation: None,
+            aspa_validation: None,
+        }
+    }
+}
+
+impl BgpElem {
+    /// Check whether any of the origin ASNs falls into a reserved ASN range.
+    ///
+    /// Returns `None` if the origin ASNs are unknown. See [is_reserved_asn] for the ranges.
+    pub fn has_bogon_origin(&self) -> Option<bool> {
+        self.origin_asns.as_ref().map(|asns| asns.iter().any(|asn| is_reserved_asn(*asn)))
+    }
+
+    /// Check whether the MULTI_EXIT_DISC attribute is present, including an explicit zero.
+    pub fn has_med(&self) -> bool {
+        self.med.is_some()
+    }
+
+    /// Check whether the LOCAL_PREF attribute is present, including an explicit zero.
+    pub fn has_local_pref(&self) -> bool {
+        self.local_pref.is_some()
+    }
+
+    /// Check whether the element is for exactly the given prefix, including the `path_id`.
+    pub fn matches_prefix(&self, prefix: &NetworkPrefix) -> bool {
+        self.prefix == *prefix
+    }
+
+    /// Check whether the element carries the given community. Returns `false` if the element has
+    /// no communities.
+    pub fn has_community(&self, community: &MetaCommunity) -> bool {
+        self.communities.as_ref().map_or(false, |v| v.contains(community))
+    }
+
+    /// Check whether the given ASN is one of the origin ASNs. Returns `false` if the origin ASNs
+    /// are unknown.
+    pub fn has_origin_asn(&self, asn: Asn) -> bool {
+        self.origin_asns.as_ref().map_or(false, |v| v.contains(&asn))
+    }
+
+    /// Check whether the AS path contains the given ASN, see [AsPath::contains_asn]. Returns
+    /// `false` if the element has no AS path.
+    pub fn path_contains(&self, asn: Asn) -> bool {
+        self.as_path.as_ref().map_or(false, |path| path.contains_asn(asn))
+    }
+
+    /// Check whether any ASN on the AS path is in the given set.
+    ///
+    /// All segments are checked, as with [AsPath::contains_asn]. Returns `false` if the element
+    /// has no AS path.
+    pub fn path_intersects(&self, asns: &HashSet<Asn>) -> bool {
+        self.as_path.as_ref().map_or(false, |p| p.iter_asns().any(|a| asns.contains(&a)))
+    }
+
+    /// Set the attribute fields of the element from a list of BGP attributes.
+    ///
+    /// AS4_PATH is merged into AS_PATH when both are present, and `origin_asns` is derived from
+    /// the resulting path. The next hop is resolved with [resolve_next_hop]. Regular, extended
+    /// and large communities are collected into `communities`. Fields of attributes that are not
+    /// present are left unchanged.
+    pub fn set_attributes(&mut self, attrs: &[Attribute]) {
+        let mut as_path: Option<&AsPath> = None;
+        let mut as4_path: Option<&AsPath> = None;
+        let mut communities: Vec<MetaCommunity> = vec![];
+        for attr in attrs {
+            match &attr.value {
+                AttributeValue::Origin(v) => self.origin = Some(*v),
+                AttributeValue::AsPath(v) => as_path = Some(v),
+                AttributeValue::As4Path(v) => as4_path = Some(v),
+                AttributeValue::MultiExitDiscriminator(v) => self.med = Some(*v),
+                AttributeValue::LocalPreference(v) => self.local_pref = Some(*v),
+                AttributeValue::AtomicAggregate(v) => self.atomic = Some(*v),
+                AttributeValue::Aggregator(asn, ip) => {
+                    self.aggr_asn = Some(*asn);
+                    self.aggr_ip = Some(*ip);
+                }
+                AttributeValue::Communities(v) => {
+                    communities.extend(v.iter().map(|c| MetaCommunity::Community(*c)))
+                }
+                AttributeValue::ExtendedCommunities(v) => {
+                    communities.extend(v.iter().map(|c| MetaCommunity::ExtendedCommunity(*c)))
+                }
+                AttributeValue::LargeCommunities(v) => {
+                    communities.extend(v.iter().map(|c| MetaCommunity::LargeCommunity(*c)))
+                }
+                AttributeValue::OriginatorId(v) => self.originator_id = Some(*v),
+                AttributeValue::Clusters(v) => self.cluster_list = Some(v.clone()),
+                _ => {}
+            }
+        }
+
+        let path = match (as_path, as4_path) {
+            (Some(p), Some(p4)) => AsPath::merge_aspath_as4path(p, p4),
+            (Some(p), None) | (None, Some(p)) => Some(p.clone()),
+            (None, None) => None,
+        };
+        if let Some(path) = path {
+            self.origin_asns = path.origin().map(OriginAsns::from);
+            self.as_path = Some(path);
+        }
+        if let Some(next_hop) = resolve_next_hop(attrs) {
+            self.next_hop = Some(next_hop);
+        }
+        if !communities.is_empty() {
+            self.communities = Some(communities);
+        }
+    }
+
+    /// Fill every `None` optional field with the value from `base`, e.g. to merge a partial
+    /// update into a complete element.
+    ///
+    /// Only the optional attribute fields (`next_hop`, `as_path`, `communities`, etc.) are filled.
+    /// Fields that are already set are kept, and the required fields, including `elem_type`, are
+    /// never changed, so a `WITHDRAW` element stays a withdrawal.
+    pub fn fill_from(&mut self, base: &BgpElem) {
+        macro_rules! fill {
+            ($($field:ident),*) => {
+                $(
+                    if self.$field.is_none() {
+                        self.$field = base.$field.clone();
+                    }
+                )*
+            }
+        }
+        fill!(next_hop, as_path, origin_asns, origin, local_pref, med, communities, atomic,
+              aggr_asn, aggr_ip, cluster_list, originator_id, origin_validation, aspa_validation);
+    }
+
+    /// Merge the optional fields of `other` into the element, see [BgpElem::fill_from].
+    ///
+    /// Only absent (`None`) fields take the value of `other`. A field set to an explicit zero,
+    /// e.g. `med: Some(0)`, is present and kept.
+    pub fn merge_from(&mut self, other: &BgpElem) {
+        self.fill_from(other)
+    }
+
+    /// Cache the RPKI route origin validation result of the element.
+    pub fn set_origin_validation(&mut self, state: RpkiValidationState) {
+        self.origin_validation = Some(state);
+    }
+
+    /// Cache the ASPA AS path validation result of the element.
+    pub fn set_aspa_validation(&mut self, state: AspaValidationState) {
+        self.aspa_validation = Some(state);
+    }
+
+    /// Get the [RouteKey] of the element, i.e. the prefix and the peer it is learned from.
+    pub fn route_key(&self) -> RouteKey {
+        RouteKey {
+            prefix: self.prefix,
+            peer_ip: self.peer_ip,
+            peer_asn: self.peer_asn,
         }
     }
 }
 
+/// Key identifying a route by its prefix and the peer it is learned from.
+///
+/// `path_id` of the prefix is part of the key, so ADD-PATH routes of the same prefix from the
+/// same peer are distinct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct RouteKey {
+    pub prefix: NetworkPrefix,
+    pub peer_ip: IpAddr,
+    pub peer_asn: Asn,
+}
+
+/// Builder for [BgpElem].
+///
+/// `timestamp`, `peer_ip`, `peer_asn` and `prefix` are required, and [BgpElemBuilder::build]
+/// returns an error if any of them is missing. The element type defaults to `ANNOUNCE`.
+///
+/// ```
+/// use std::net::IpAddr;
+/// use std::str::FromStr;
+/// use bgp_models::prelude::*;
+///
+/// let elem = BgpElemBuilder::new()
+///     .timestamp(1.0)
+///     .elem_type(ElemType::ANNOUNCE)
+///     .peer_ip(IpAddr::from_str("192.168.1.1").unwrap())
+///     .peer_asn(65000.into())
+///     .prefix(NetworkPrefix::from_str("8.8.8.0/24").unwrap())
+///     .med(100)
+///     .build()
+///     .unwrap();
+/// assert_eq!(elem.med, Some(100));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BgpElemBuilder {
+    timestamp: Option<f64>,
+    elem_type: Option<ElemType>,
+    peer_ip: Option<IpAddr>,
+    peer_asn: Option<Asn>,
+    prefix: Option<NetworkPrefix>,
+    next_hop: Option<IpAddr>,
+    as_path: Option<AsPath>,
+    origin_asns: Option<OriginAsns>,
+    origin: Option<Origin>,
+    local_pref: Option<u32>,
+    med: Option<u32>,
+    communities: Option<Vec<MetaCommunity>>,
+    atomic: Option<AtomicAggregate>,
+    aggr_asn: Option<Asn>,
+    aggr_ip: Option<IpAddr>,
+    cluster_list: Option<Vec<IpAddr>>,
+    originator_id: Option<IpAddr>,
+    origin_validation: Option<RpkiValidationState>,
+    aspa_validation: Option<AspaValidationState>,
+}
+
+macro_rules! builder_setter {
+    ($name:ident, $t:ty) => {
+        pub fn $name(mut self, $name: $t) -> Self {
+            self.$name = Some($name);
+            self
+        }
+    }
+}
+
+impl BgpElemBuilder {
+    pub fn new() -> BgpElemBuilder {
+        BgpElemBuilder::default()
+    }
+
+    builder_setter!(timestamp, f64);
+    builder_setter!(elem_type, ElemType);
+    builder_setter!(peer_ip, IpAddr);
+    builder_setter!(peer_asn, Asn);
+    builder_setter!(prefix, NetworkPrefix);
+    builder_setter!(next_hop, IpAddr);
+    builder_setter!(as_path, AsPath);
+    builder_setter!(origin_asns, OriginAsns);
+    builder_setter!(origin, Origin);
+    builder_setter!(local_pref, u32);
+    builder_setter!(med, u32);
+    builder_setter!(communities, Vec<MetaCommunity>);
+    builder_setter!(atomic, AtomicAggregate);
+    builder_setter!(aggr_asn, Asn);
+    builder_setter!(aggr_ip, IpAddr);
+    builder_setter!(cluster_list, Vec<IpAddr>);
+    builder_setter!(originator_id, IpAddr);
+    builder_setter!(origin_validation, RpkiValidationState);
+    builder_setter!(aspa_validation, AspaValidationState);
+
+    /// Build the [BgpElem], checking that all required fields are set.
+    pub fn build(self) -> Result<BgpElem, BgpModelsError> {
+        let missing = |field: &str| BgpModelsError::ElemBuildingError(format!("missing required field {}", field));
+        Ok(BgpElem {
+            timestamp: self.timestamp.ok_or_else(|| missing("timestamp"))?,
+            elem_type: self.elem_type.unwrap_or(ElemType::ANNOUNCE),
+            peer_ip: self.peer_ip.ok_or_else(|| missing("peer_ip"))?,
+            peer_asn: self.peer_asn.ok_or_else(|| missing("peer_asn"))?,
+            prefix: self.prefix.ok_or_else(|| missing("prefix"))?,
+            next_hop: self.next_hop,
+            as_path: self.as_path,
+            origin_asns: self.origin_asns,
+            origin: self.origin,
+            local_pref: self.local_pref,
+            med: self.med,
+            communities: self.communities,
+            atomic: self.atomic,
+            aggr_asn: self.aggr_asn,
+            aggr_ip: self.aggr_ip,
+            cluster_list: self.cluster_list,
+            originator_id: self.originator_id,
+            origin_validation: self.origin_validation,
+            aspa_validation: self.aspa_validation,
+        })
+    }
+}
+
+/// A group of [BgpElem]s that share all fields except the prefix.
+///
+/// `elem` holds the shared fields, and its `prefix` is the first prefix of the group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElemGroup {
+    pub elem: BgpElem,
+    pub prefixes: Vec<NetworkPrefix>,
+}
+
+/// Memory-saving representation of a list of [BgpElem]s.
+///
+/// Elements from the same peer with identical attributes (AS path, communities, origin, next hop,
+/// etc.) are coalesced into one [ElemGroup], storing the shared attributes once with the list of
+/// prefixes. Elements are grouped only if all fields other than the prefix are equal, so
+/// [CoalescedElems::iter] re-expands the original elements without loss. The groups keep the
+/// order in which they are first seen.
+#[derive(Debug, Clone, Default)]
+pub struct CoalescedElems {
+    groups: Vec<ElemGroup>,
+    index: HashMap<u64, Vec<usize>>,
+}
+
+/// Check whether two elements are equal in all fields except the prefix.
+fn same_shared_fields(a: &BgpElem, b: &BgpElem) -> bool {
+    a.timestamp == b.timestamp
+        && a.elem_type == b.elem_type
+        && a.peer_ip == b.peer_ip
+        && a.peer_asn == b.peer_asn
+        && a.next_hop == b.next_hop
+        && a.as_path == b.as_path
+        && a.origin_asns == b.origin_asns
+        && a.origin == b.origin
+        && a.local_pref == b.local_pref
+        && a.med == b.med
+        && a.communities == b.communities
+        && a.atomic == b.atomic
+        && a.aggr_asn == b.aggr_asn
+        && a.aggr_ip == b.aggr_ip
+        && a.cluster_list == b.cluster_list
+        && a.originator_id == b.originator_id
+        && a.origin_validation == b.origin_validation
+        && a.aspa_validation == b.aspa_validation
+}
+
+fn hash_shared_fields(elem: &BgpElem) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    elem.timestamp.to_bits().hash(&mut hasher);
+    elem.elem_type.hash(&mut hasher);
+    elem.peer_ip.hash(&mut hasher);
+    elem.peer_asn.hash(&mut hasher);
+    elem.next_hop.hash(&mut hasher);
+    elem.as_path.hash(&mut hasher);
+    elem.origin_asns.hash(&mut hasher);
+    elem.origin.hash(&mut hasher);
+    elem.local_pref.hash(&mut hasher);
+    elem.med.hash(&mut hasher);
+    elem.communities.hash(&mut hasher);
+    elem.atomic.hash(&mut hasher);
+    elem.aggr_asn.hash(&mut hasher);
+    elem.aggr_ip.hash(&mut hasher);
+    elem.cluster_list.hash(&mut hasher);
+    elem.originator_id.hash(&mut hasher);
+    elem.origin_validation.hash(&mut hasher);
+    elem.aspa_validation.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl CoalescedElems {
+    pub fn new() -> CoalescedElems {
+        CoalescedElems::default()
+    }
+
+    /// Add an element, merging it into an existing group if one shares its attributes.
+    pub fn push(&mut self, elem: BgpElem) {
+        let hash = hash_shared_fields(&elem);
+        let candidates = self.index.entry(hash).or_default();
+        for i in candidates.iter() {
+            let group = &mut self.groups[*i];
+            if same_shared_fields(&group.elem, &elem) {
+                group.prefixes.push(elem.prefix);
+                return
+            }
+        }
+        candidates.push(self.groups.len());
+        self.groups.push(ElemGroup { prefixes: vec![elem.prefix], elem });
+    }
+
+    pub fn groups(&self) -> &Vec<ElemGroup> {
+        &self.groups
+    }
+
+    /// Total number of elements across all groups.
+    pub fn len(&self) -> usize {
+        self.groups.iter().map(|g| g.prefixes.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Iterate over the re-expanded elements, group by group.
+    pub fn iter(&self) -> impl Iterator<Item = BgpElem> + '_ {
+        self.groups.iter().flat_map(|group| {
+            group.prefixes.iter().map(move |prefix| BgpElem {
+                prefix: *prefix,
+                ..group.elem.clone()
+            })
+        })
+    }
+}
+
+impl std::iter::FromIterator<BgpElem> for CoalescedElems {
+    fn from_iter<T: IntoIterator<Item = BgpElem>>(iter: T) -> Self {
+        let mut coalesced = CoalescedElems::new();
+        for elem in iter {
+            coalesced.push(elem);
+        }
+        coalesced
+    }
+}
+
+/// Running statistics over a stream of [BgpElem]s.
+///
+/// Prefixes are counted by network only, ignoring the ADDPATH `path_id`, and peers by their
+/// address and ASN. Origin ASN counts only include announcements; each origin of a multi-origin
+/// (AS_SET) announcement is counted once.
+#[derive(Debug, Clone, Default)]
+pub struct ElemStats {
+    pub announcements: usize,
+    pub withdrawals: usize,
+    pub prefixes: HashSet<IpNetwork>,
+    pub peers: HashSet<(IpAddr, Asn)>,
+    pub origin_announcements: HashMap<Asn, usize>,
+}
+
+impl ElemStats {
+    pub fn new() -> ElemStats {
+        ElemStats::default()
+    }
+
+    /// Add an element to the statistics.
+    pub fn observe(&mut self, elem: &BgpElem) {
+        match elem.elem_type {
+            ElemType::ANNOUNCE => {
+                self.announcements += 1;
+                if let Some(origins) = &elem.origin_asns {
+                    for asn in origins.iter().unique() {
+                        *self.origin_announcements.entry(*asn).or_default() += 1;
+                    }
+                }
+            }
+            ElemType::WITHDRAW => self.withdrawals += 1,
+        }
+        self.prefixes.insert(elem.prefix.prefix);
+        self.peers.insert((elem.peer_ip, elem.peer_asn));
+    }
+
+    /// One-line summary of the counts, e.g.
+    /// `3 announcements, 1 withdrawals, 2 prefixes, 2 peers, 1 origins`.
+    pub fn summary(&self) -> String {
+        format!("{} announcements, {} withdrawals, {} prefixes, {} peers, {} origins",
+                self.announcements, self.withdrawals, self.prefixes.len(), self.peers.len(),
+                self.origin_announcements.len())
+    }
+}
+
+//////////////////
+// BINARY CODEC //
+//////////////////
+
+const FIELD_NEXT_HOP: u16 = 1;
+const FIELD_AS_PATH: u16 = 1 << 1;
+const FIELD_ORIGIN_ASNS: u16 = 1 << 2;
+const FIELD_ORIGIN: u16 = 1 << 3;
+const FIELD_LOCAL_PREF: u16 = 1 << 4;
+const FIELD_MED: u16 = 1 << 5;
+const FIELD_COMMUNITIES: u16 = 1 << 6;
+const FIELD_ATOMIC: u16 = 1 << 7;
+const FIELD_AGGR_ASN: u16 = 1 << 8;
+const FIELD_AGGR_IP: u16 = 1 << 9;
+const FIELD_CLUSTER_LIST: u16 = 1 << 10;
+const FIELD_ORIGINATOR_ID: u16 = 1 << 11;
+const FIELD_ORIGIN_VALIDATION: u16 = 1 << 12;
+const FIELD_ASPA_VALIDATION: u16 = 1 << 13;
+
+impl BgpElem {
+    /// Encode the element into a compact binary form, independent of serde.
+    ///
+    /// All integers are big-endian. The layout is:
+    ///
+    /// | field          | encoding                                                      |
+    /// |----------------|---------------------------------------------------------------|
+    /// | `timestamp`    | f64                                                           |
+    /// | `elem_type`    | u8, see [ElemType::code]                                      |
+    /// | `peer_ip`      | IP                                                            |
+    /// | `peer_asn`     | u32                                                           |
+    /// | `prefix`       | IP, prefix length u8, path ID u32                             |
+    /// | presence flags | u16, bit `i` set if the `i`-th optional field below is present |
+    ///
+    /// The present optional fields follow in order: `next_hop` (IP), `as_path`, `origin_asns`
+    /// (u32 count, u32 each), `origin` (u8), `local_pref` (u32), `med` (u32), `communities`,
+    /// `atomic` (u8), `aggr_asn` (u32), `aggr_ip` (IP), `cluster_list` (u32 count, IP each),
+    /// `originator_id` (IP), `origin_validation` (u8, see [RpkiValidationState]) and
+    /// `aspa_validation` (u8, see [AspaValidationState]).
+    ///
+    /// - IP: u8 version (`4` or `6`) followed by 4 or 16 bytes.
+    /// - AS path: u32 segment count, then per segment u8 type (`1` AS_SET, `2` AS_SEQUENCE,
+    ///   `3` AS_CONFED_SEQUENCE, `4` AS_CONFED_SET), u32 ASN count and u32 ASNs.
+    /// - communities: u32 count, then per community a u8 tag and its fields. Tags `0`-`3` are the
+    ///   [Community] variants, `16`-`25` the [ExtendedCommunity] variants and `32` is
+    ///   [LargeCommunity].
+    ///
+    /// The ASN length of [Asn]s is not preserved; decoded ASNs are 32-bit.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf: Vec<u8> = vec![];
+        buf.extend_from_slice(&self.timestamp.to_be_bytes());
+        buf.push(self.elem_type.code());
+        put_ip(&mut buf, &self.peer_ip);
+        buf.extend_from_slice(&self.peer_asn.asn.to_be_bytes());
+        put_ip(&mut buf, &self.prefix.prefix.ip());
+        buf.push(self.prefix.prefix.prefix());
+        buf.extend_from_slice(&self.prefix.path_id.to_be_bytes());
+
+        let flags_pos = buf.len();
+        let mut flags: u16 = 0;
+        buf.extend_from_slice(&[0, 0]);
+
+        if let Some(v) = &self.next_hop {
+            flags |= FIELD_NEXT_HOP;
+            put_ip(&mut buf, v);
+        }
+        if let Some(v) = &self.as_path {
+            flags |= FIELD_AS_PATH;
+            buf.extend_from_slice(&(v.segments.len() as u32).to_be_bytes());
+            for seg in &v.segments {
+                let (seg_type, asns) = match seg {
+                    AsPathSegment::AsSet(v) => (1, v),
+                    AsPathSegment::AsSequence(v) => (2, v),
+                    AsPathSegment::ConfedSequence(v) => (3, v),
+                    AsPathSegment::ConfedSet(v) => (4, v),
+                };
+                buf.push(seg_type);
+                put_asns(&mut buf, asns);
+            }
+        }
+        if let Some(v) = &self.origin_asns {
+            flags |= FIELD_ORIGIN_ASNS;
+            put_asns(&mut buf, v.as_slice());
+        }
+        if let Some(v) = &self.origin {
+            flags |= FIELD_ORIGIN;
+            buf.push(*v as u8);
+        }
+        if let Some(v) = &self.local_pref {
+            flags |= FIELD_LOCAL_PREF;
+            buf.extend_from_slice(&v.to_be_bytes());
+        }
+        if let Some(v) = &self.med {
+            flags |= FIELD_MED;
+            buf.extend_from_slice(&v.to_be_bytes());
+        }
+        if let Some(v) = &self.communities {
+            flags |= FIELD_COMMUNITIES;
+            buf.extend_from_slice(&(v.len() as u32).to_be_bytes());
+            for c in v {
+                put_community(&mut buf, c);
+            }
+        }
+        if let Some(v) = &self.atomic {
+            flags |= FIELD_ATOMIC;
+            buf.push(*v as u8);
+        }
+        if let Some(v) = &self.aggr_asn {
+            flags |= FIELD_AGGR_ASN;
+            buf.extend_from_slice(&v.asn.to_be_bytes());
+        }
+        if let Some(v) = &self.aggr_ip {
+            flags |= FIELD_AGGR_IP;
+            put_ip(&mut buf, v);
+        }
+        if let Some(v) = &self.cluster_list {
+            flags |= FIELD_CLUSTER_LIST;
+            buf.extend_from_slice(&(v.len() as u32).to_be_bytes());
+            for ip in v {
+                put_ip(&mut buf, ip);
+            }
+        }
+        if let Some(v) = &self.originator_id {
+            flags |= FIELD_ORIGINATOR_ID;
+            put_ip(&mut buf, v);
+        }
+        if let Some(v) = &self.origin_validation {
+            flags |= FIELD_ORIGIN_VALIDATION;
+            buf.push(*v as u8);
+        }
+        if let Some(v) = &self.aspa_validation {
+            flags |= FIELD_ASPA_VALIDATION;
+            buf.push(*v as u8);
+        }
+
+        buf[flags_pos..flags_pos + 2].copy_from_slice(&flags.to_be_bytes());
+        buf
+    }
+
+    /// Decode an element from the binary form produced by [BgpElem::to_bytes].
+    pub fn from_bytes(data: &[u8]) -> Result<BgpElem, BgpModelsError> {
+        let mut r = ElemReader { data };
+        let timestamp = f64::from_be_bytes(r.array::<[u8; 8]>()?);
+        let elem_type = ElemType::from_code(r.u8()?)
+            .ok_or_else(|| decoding_error("unknown element type"))?;
+        let peer_ip = r.ip()?;
+        let peer_asn = Asn::from(r.u32()?);
+        let prefix_ip = r.ip()?;
+        let prefix_len = r.u8()?;
+        let prefix = NetworkPrefix::new(
+            IpNetwork::new(prefix_ip, prefix_len).map_err(|e| decoding_error(&e.to_string()))?,
+            r.u32()?,
+        );
+        let flags = r.u16()?;
+        let has = |field: u16| flags & field != 0;
+
+        let mut elem = BgpElem { timestamp, elem_type, peer_ip, peer_asn, prefix, ..Default::default() };
+        if has(FIELD_NEXT_HOP) {
+            elem.next_hop = Some(r.ip()?);
+        }
+        if has(FIELD_AS_PATH) {
+            let count = r.u32()?;
+            let mut segments = vec![];
+            for _ in 0..count {
+                let seg_type = r.u8()?;
+                let asns = r.asns()?;
+                segments.push(match seg_type {
+                    1 => AsPathSegment::AsSet(asns),
+                    2 => AsPathSegment::AsSequence(asns),
+                    3 => AsPathSegment::ConfedSequence(asns),
+                    4 => AsPathSegment::ConfedSet(asns),
+                    t => return Err(decoding_error(&format!("unknown AS path segment type {}", t))),
+                });
+            }
+            elem.as_path = Some(AsPath { segments });
+        }
+        if has(FIELD_ORIGIN_ASNS) {
+            elem.origin_asns = Some(r.asns()?.into());
+        }
+        if has(FIELD_ORIGIN) {
+            elem.origin = Some(Origin::from_u8(r.u8()?).ok_or_else(|| decoding_error("unknown origin"))?);
+        }
+        if has(FIELD_LOCAL_PREF) {
+            elem.local_pref = Some(r.u32()?);
+        }
+        if has(FIELD_MED) {
+            elem.med = Some(r.u32()?);
+        }
+        if has(FIELD_COMMUNITIES) {
+            let count = r.u32()?;
+            let mut communities = vec![];
+            for _ in 0..count {
+                communities.push(r.community()?);
+            }
+            elem.communities = Some(communities);
+        }
+        if has(FIELD_ATOMIC) {
+            elem.atomic = Some(AtomicAggregate::from_u8(r.u8()?)
+                .ok_or_else(|| decoding_error("unknown atomic aggregate"))?);
+        }
+        if has(FIELD_AGGR_ASN) {
+            elem.aggr_asn = Some(Asn::from(r.u32()?));
+        }
+        if has(FIELD_AGGR_IP) {
+            elem.aggr_ip = Some(r.ip()?);
+        }
+        if has(FIELD_CLUSTER_LIST) {
+            let count = r.u32()?;
+            let mut ips = vec![];
+            for _ in 0..count {
+                ips.push(r.ip()?);
+            }
+            elem.cluster_list = Some(ips);
+        }
+        if has(FIELD_ORIGINATOR_ID) {
+            elem.originator_id = Some(r.ip()?);
+        }
+        if has(FIELD_ORIGIN_VALIDATION) {
+            elem.origin_validation = Some(RpkiValidationState::from_u8(r.u8()?)
+                .ok_or_else(|| decoding_error("unknown origin validation state"))?);
+        }
+        if has(FIELD_ASPA_VALIDATION) {
+            elem.aspa_validation = Some(AspaValidationState::from_u8(r.u8()?)
+                .ok_or_else(|| decoding_error("unknown ASPA validation state"))?);
+        }
+        if !r.data.is_empty() {
+            return Err(decoding_error("trailing bytes"))
+        }
+        Ok(elem)
+    }
+}
+
+fn decoding_error(msg: &str) -> BgpModelsError {
+    BgpModelsError::ElemDecodingError(msg.to_string())
+}
+
+fn put_ip(buf: &mut Vec<u8>, ip: &IpAddr) {
+    match ip {
+        IpAddr::V4(v) => {
+            buf.push(4);
+            buf.extend_from_slice(&v.octets());
+        }
+        IpAddr::V6(v) => {
+            buf.push(6);
+            buf.extend_from_slice(&v.octets());
+        }
+    }
+}
+
+fn put_asns(buf: &mut Vec<u8>, asns: &[Asn]) {
+    buf.extend_from_slice(&(asns.len() as u32).to_be_bytes());
+    for asn in asns {
+        buf.extend_from_slice(&asn.asn.to_be_bytes());
+    }
+}
+
+fn put_community(buf: &mut Vec<u8>, community: &MetaCommunity) {
+    match community {
+        MetaCommunity::Community(c) => match c {
+            Community::NoExport => buf.push(0),
+            Community::NoAdvertise => buf.push(1),
+            Community::NoExportSubConfed => buf.push(2),
+            Community::Custom(asn, value) => {
+                buf.push(3);
+                buf.extend_from_slice(&asn.asn.to_be_bytes());
+                buf.extend_from_slice(&value.to_be_bytes());
+            }
+        },
+        MetaCommunity::ExtendedCommunity(c) => match c {
+            ExtendedCommunity::TransitiveTwoOctetAsSpecific(ec) |
+            ExtendedCommunity::NonTransitiveTwoOctetAsSpecific(ec) => {
+                buf.push(if let ExtendedCommunity::TransitiveTwoOctetAsSpecific(_) = c { 16 } else { 20 });
+                buf.extend_from_slice(&[ec.ec_type, ec.ec_subtype]);
+                buf.extend_from_slice(&ec.global_administrator.asn.to_be_bytes());
+                buf.extend_from_slice(&ec.local_administrator);
+            }
+            ExtendedCommunity::TransitiveIpv4AddressSpecific(ec) |
+            ExtendedCommunity::NonTransitiveIpv4AddressSpecific(ec) => {
+                buf.push(if let ExtendedCommunity::TransitiveIpv4AddressSpecific(_) = c { 17 } else { 21 });
+                buf.extend_from_slice(&[ec.ec_type, ec.ec_subtype]);
+                buf.extend_from_slice(&ec.global_administrator.octets());
+                buf.extend_from_slice(&ec.local_administrator);
+            }
+            ExtendedCommunity::TransitiveFourOctetAsSpecific(ec) |
+            ExtendedCommunity::NonTransitiveFourOctetAsSpecific(ec) => {
+                buf.push(if let ExtendedCommunity::TransitiveFourOctetAsSpecific(_) = c { 18 } else { 22 });
+                buf.extend_from_slice(&[ec.ec_type, ec.ec_subtype]);
+                buf.extend_from_slice(&ec.global_administrator.asn.to_be_bytes());
+                buf.extend_from_slice(&ec.local_administrator);
+            }
+            ExtendedCommunity::TransitiveOpaque(ec) |
+            ExtendedCommunity::NonTransitiveOpaque(ec) => {
+                buf.push(if let ExtendedCommunity::TransitiveOpaque(_) = c { 19 } else { 23 });
+                buf.extend_from_slice(&[ec.ec_type, ec.ec_subtype]);
+                buf.extend_from_slice(&ec.value);
+            }
+            ExtendedCommunity::Ipv6AddressSpecific(ec) => {
+                buf.push(24);
+                buf.extend_from_slice(&ec.to_bytes());
+            }
+            ExtendedCommunity::Raw(v) => {
+                buf.push(25);
+                buf.extend_from_slice(v);
+            }
+        },
+        MetaCommunity::LargeCommunity(c) => {
+            buf.push(32);
+            buf.extend_from_slice(&c.global_administrator.to_be_bytes());
+            buf.extend_from_slice(&c.local_data[0].to_be_bytes());
+            buf.extend_from_slice(&c.local_data[1].to_be_bytes());
+        }
+    }
+}
+
+/// Cursor over the bytes of an encoded [BgpElem].
+struct ElemReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ElemReader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], BgpModelsError> {
+        if self.data.len() < n {
+            return Err(decoding_error("unexpected end of data"))
+        }
+        let (head, rest) = self.data.split_at(n);
+        self.data = rest;
+        Ok(head)
+    }
+
+    /// Read a byte array, e.g. `[u8; 4]`, whose size gives the number of bytes to read.
+    fn array<T: TryFrom<&'a [u8]>>(&mut self) -> Result<T, BgpModelsError> {
+        let bytes = self.take(std::mem::size_of::<T>())?;
+        T::try_from(bytes).map_err(|_| decoding_error("invalid array length"))
+    }
+
+    fn u8(&mut self) -> Result<u8, BgpModelsError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, BgpModelsError> {
+        Ok(u16::from_be_bytes(self.array::<[u8; 2]>()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, BgpModelsError> {
+        Ok(u32::from_be_bytes(self.array::<[u8; 4]>()?))
+    }
+
+    fn ip(&mut self) -> Result<IpAddr, BgpModelsError> {
+        match self.u8()? {
+            4 => Ok(IpAddr::V4(Ipv4Addr::from(self.array::<[u8; 4]>()?))),
+            6 => Ok(IpAddr::V6(Ipv6Addr::from(self.array::<[u8; 16]>()?))),
+            v => Err(decoding_error(&format!("unknown IP version {}", v))),
+        }
+    }
+
+    fn asns(&mut self) -> Result<Vec<Asn>, BgpModelsError> {
+        let count = self.u32()?;
+        let mut asns = vec![];
+        for _ in 0..count {
+            asns.push(Asn::from(self.u32()?));
+        }
+        Ok(asns)
+    }
+
+    fn community(&mut self) -> Result<MetaCommunity, BgpModelsError> {
+        let tag = self.u8()?;
+        let community = match tag {
+            0 => MetaCommunity::Community(Community::NoExport),
+            1 => MetaCommunity::Community(Community::NoAdvertise),
+            2 => MetaCommunity::Community(Community::NoExportSubConfed),
+            3 => MetaCommunity::Community(Community::Custom(Asn::from(self.u32()?), self.u16()?)),
+            16 | 20 => {
+                let ec = TwoOctetAsSpecific {
+                    ec_type: self.u8()?,
+                    ec_subtype: self.u8()?,
+                    global_administrator: Asn::from(self.u32()?),
+                    local_administrator: self.array::<[u8; 4]>()?,
+                };
+                MetaCommunity::ExtendedCommunity(match tag {
+                    16 => ExtendedCommunity::TransitiveTwoOctetAsSpecific(ec),
+                    _ => ExtendedCommunity::NonTransitiveTwoOctetAsSpecific(ec),
+                })
+            }
+            17 | 21 => {
+                let ec = Ipv4AddressSpecific {
+                    ec_type: self.u8()?,
+                    ec_subtype: self.u8()?,
+                    global_administrator: Ipv4Addr::from(self.array::<[u8; 4]>()?),
+                    local_administrator: self.array::<[u8; 2]>()?,
+                };
+                MetaCommunity::ExtendedCommunity(match tag {
+                    17 => ExtendedCommunity::TransitiveIpv4AddressSpecific(ec),
+                    _ => ExtendedCommunity::NonTransitiveIpv4AddressSpecific(ec),
+                })
+            }
+            18 | 22 => {
+                let ec = FourOctetAsSpecific {
+                    ec_type: self.u8()?,
+                    ec_subtype: self.u8()?,
+                    global_administrator: Asn::from(self.u32()?),
+                    local_administrator: self.array::<[u8; 2]>()?,
+                };
+                MetaCommunity::ExtendedCommunity(match tag {
+                    18 => ExtendedCommunity::TransitiveFourOctetAsSpecific(ec),
+                    _ => ExtendedCommunity::NonTransitiveFourOctetAsSpecific(ec),
+                })
+            }
+            19 | 23 => {
+                let ec = Opaque {
+                    ec_type: self.u8()?,
+                    ec_subtype: self.u8()?,
+                    value: self.array::<[u8; 6]>()?,
+                };
+                MetaCommunity::ExtendedCommunity(match tag {
+                    19 => ExtendedCommunity::TransitiveOpaque(ec),
+                    _ => ExtendedCommunity::NonTransitiveOpaque(ec),
+                })
+            }
+            24 => {
+                let ec = Ipv6AddressSpecific::try_from(self.take(20)?)?;
+                MetaCommunity::ExtendedCommunity(ExtendedCommunity::Ipv6AddressSpecific(ec))
+            }
+            25 => MetaCommunity::ExtendedCommunity(ExtendedCommunity::Raw(self.array::<[u8; 8]>()?)),
+            32 => MetaCommunity::LargeCommunity(LargeCommunity::new(self.u32()?, [self.u32()?, self.u32()?])),
+            t => return Err(decoding_error(&format!("unknown community tag {}", t))),
+        };
+        Ok(community)
+    }
+}
+
 macro_rules! option_to_string{
     ($a:expr) => {
         if let Some(v) = $a {
@@ -129,6 +1152,16 @@ pub fn option_to_string_communities(o: &Option<Vec<MetaCommunity>>) -> String {
     }
 }
 
+#[inline(always)]
+pub fn option_to_string_ips(o: &Option<Vec<IpAddr>>) -> String {
+    if let Some(v) = o {
+        v.iter()
+            .join(" ")
+    } else {
+        String::new()
+    }
+}
+
 impl Display for BgpElem {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let t = match self.elem_type {
@@ -136,7 +1169,7 @@ impl Display for BgpElem {
             ElemType::WITHDRAW => "W",
         };
         let format = format!(
-            "{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}",
+            "{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}|{}",
             t, &self.timestamp,
             &self.peer_ip,
             &self.peer_asn,
@@ -150,6 +1183,8 @@ impl Display for BgpElem {
             option_to_string!(&self.atomic),
             option_to_string!(&self.aggr_asn),
             option_to_string!(&self.aggr_ip),
+            option_to_string_ips(&self.cluster_list),
+            option_to_string!(&self.originator_id),
         );
         write!(f, "{}", format)
     }
@@ -174,6 +1209,33 @@ mod tests {
         println!("{}",serde_json::json!(elem).to_string());
     }
 
+    #[test]
+    fn test_route_key() {
+        let elem1 = BgpElem{
+            peer_ip: IpAddr::from_str("192.168.1.1").unwrap(),
+            peer_asn: 65000.into(),
+            prefix: NetworkPrefix::from_str("8.8.8.0/24").unwrap(),
+            ..Default::default()
+        };
+        let elem2 = BgpElem{
+            peer_ip: IpAddr::from_str("192.168.1.2").unwrap(),
+            ..elem1.clone()
+        };
+        let elem3 = BgpElem{
+            timestamp: 10.0,
+            elem_type: ElemType::WITHDRAW,
+            ..elem1.clone()
+        };
+        assert_ne!(elem1.route_key(), elem2.route_key());
+        assert_eq!(elem1.route_key(), elem3.route_key());
+
+        let mut map: HashMap<RouteKey, BgpElem> = HashMap::new();
+        for elem in [elem1, elem2, elem3] {
+            map.insert(elem.route_key(), elem);
+        }
+        assert_eq!(map.len(), 2);
+    }
+
     #[test]
     fn test_sorting() {
         let elem1 = BgpElem{
@@ -204,4 +1266,388 @@ mod tests {
         assert_eq!(elem1<elem2, true);
         assert_eq!(elem2<elem3, true);
     }
+
+    #[test]
+    fn test_cluster_list_display() {
+        let elem = BgpElem{
+            timestamp: 1.0,
+            peer_ip: IpAddr::from_str("192.168.1.1").unwrap(),
+            peer_asn: 65000.into(),
+            prefix: NetworkPrefix::from_str("8.8.8.0/24").unwrap(),
+            cluster_list: Some(vec![IpAddr::from_str("10.0.0.1").unwrap(), IpAddr::from_str("10.0.0.2").unwrap()]),
+            originator_id: Some(IpAddr::from_str("10.0.0.3").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(elem.to_string(), "A|1|192.168.1.1|65000|8.8.8.0/24||||||||||10.0.0.1 10.0.0.2|10.0.0.3");
+        assert_eq!(BgpElem::default().cluster_list, None);
+        assert_eq!(BgpElem::default().originator_id, None);
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let elem = BgpElem {
+            timestamp: 1609459200.123,
+            elem_type: ElemType::ANNOUNCE,
+            peer_ip: IpAddr::from_str("2001:db8::1").unwrap(),
+            peer_asn: 65000.into(),
+            prefix: NetworkPrefix::new(IpNetwork::from_str("8.8.8.0/24").unwrap(), 7),
+            next_hop: Some(IpAddr::from_str("192.168.1.2").unwrap()),
+            as_path: Some(AsPath{
+                segments: vec![
+                    AsPathSegment::ConfedSequence([65001].map(|i|{i.into()}).to_vec()),
+                    AsPathSegment::AsSequence([65000, 3356].map(|i|{i.into()}).to_vec()),
+                    AsPathSegment::AsSet([15169, 13335].map(|i|{i.into()}).to_vec()),
+                ]
+            }),
+            origin_asns: Some(vec![15169.into(), 13335.into()].into()),
+            origin: Some(Origin::EGP),
+            local_pref: Some(100),
+            med: Some(0),
+            communities: Some(vec![
+                MetaCommunity::Community(Community::NoExport),
+                MetaCommunity::Community(Community::Custom(65000.into(), 1)),
+                MetaCommunity::ExtendedCommunity(ExtendedCommunity::from_str("ecas2:0:2:65000:00000064").unwrap()),
+                MetaCommunity::ExtendedCommunity(ExtendedCommunity::from_str("ecv4:1:2:10.0.0.1:0064").unwrap()),
+                MetaCommunity::ExtendedCommunity(ExtendedCommunity::from_str("ecas4:2:2:4200000000:0064").unwrap()),
+                MetaCommunity::ExtendedCommunity(ExtendedCommunity::from_str("ecop:67:0:000000000000").unwrap()),
+                MetaCommunity::ExtendedCommunity(ExtendedCommunity::Ipv6AddressSpecific(
+                    Ipv6AddressSpecific::route_target(Ipv6Addr::from_str("2001:db8::1").unwrap(), 1)
+                )),
+                MetaCommunity::ExtendedCommunity(ExtendedCommunity::Raw([0x80, 1, 2, 3, 4, 5, 6, 7])),
+                MetaCommunity::LargeCommunity(LargeCommunity::new(65000, [1, 2])),
+            ]),
+            atomic: Some(AtomicAggregate::AG),
+            aggr_asn: Some(15169.into()),
+            aggr_ip: Some(IpAddr::from_str("10.0.0.1").unwrap()),
+            cluster_list: Some(vec![IpAddr::from_str("10.0.0.2").unwrap()]),
+            originator_id: Some(IpAddr::from_str("10.0.0.3").unwrap()),
+            origin_validation: Some(RpkiValidationState::Invalid),
+            aspa_validation: Some(AspaValidationState::Unknown),
+        };
+        let bytes = elem.to_bytes();
+        assert_eq!(BgpElem::from_bytes(&bytes).unwrap(), elem);
+        assert!(bytes.len() < serde_json::to_string(&elem).unwrap().len());
+
+        let elem = BgpElem {
+            elem_type: ElemType::WITHDRAW,
+            peer_ip: IpAddr::from_str("192.168.1.1").unwrap(),
+            prefix: NetworkPrefix::from_str("8.8.8.0/24").unwrap(),
+            ..Default::default()
+        };
+        let bytes = elem.to_bytes();
+        // timestamp, type, peer IP, peer ASN, prefix and presence flags only
+        assert_eq!(bytes.len(), 8 + 1 + 5 + 4 + 10 + 2);
+        assert_eq!(BgpElem::from_bytes(&bytes).unwrap(), elem);
+        assert!(bytes.len() * 5 < serde_json::to_string(&elem).unwrap().len());
+
+        assert!(BgpElem::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_fill_from() {
+        let base = BgpElem {
+            timestamp: 1.0,
+            as_path: Some(AsPath::from_str("65000 15169").unwrap()),
+            med: Some(10),
+            origin_validation: Some(RpkiValidationState::Valid),
+            aspa_validation: Some(AspaValidationState::Unknown),
+            ..Default::default()
+        };
+        let mut elem = BgpElem {
+            timestamp: 2.0,
+            elem_type: ElemType::WITHDRAW,
+            med: Some(20),
+            ..Default::default()
+        };
+        elem.fill_from(&base);
+        assert_eq!(elem.as_path, base.as_path);
+        assert_eq!(elem.med, Some(20));
+        assert_eq!(elem.timestamp, 2.0);
+        assert_eq!(elem.elem_type, ElemType::WITHDRAW);
+        assert_eq!(elem.communities, None);
+        assert_eq!(elem.origin_validation, Some(RpkiValidationState::Valid));
+        assert_eq!(elem.aspa_validation, Some(AspaValidationState::Unknown));
+    }
+
+    #[test]
+    fn test_fill_from_explicit_zero() {
+        let base = BgpElem { med: Some(10), local_pref: Some(100), ..Default::default() };
+        let mut elem = BgpElem { med: Some(0), ..Default::default() };
+        assert!(elem.has_med());
+        assert!(!elem.has_local_pref());
+
+        // an explicit zero is kept, an absent value is filled
+        elem.fill_from(&base);
+        assert_eq!(elem.med, Some(0));
+        assert_eq!(elem.local_pref, Some(100));
+        assert!(elem.has_local_pref());
+    }
+
+    #[test]
+    fn test_merge_from() {
+        let other = BgpElem {
+            med: Some(10),
+            local_pref: Some(200),
+            as_path: Some(AsPath::from_str("65000 15169").unwrap()),
+            ..Default::default()
+        };
+        let mut elem = BgpElem { med: Some(0), local_pref: Some(0), ..Default::default() };
+        elem.merge_from(&other);
+        assert_eq!(elem.med, Some(0));
+        assert_eq!(elem.local_pref, Some(0));
+        assert_eq!(elem.as_path, other.as_path);
+    }
+
+    #[test]
+    fn test_predicates() {
+        let prefix = NetworkPrefix::from_str("8.8.8.0/24").unwrap();
+        let no_export = MetaCommunity::Community(Community::NoExport);
+        let mut elem = BgpElem { prefix, ..Default::default() };
+
+        assert!(elem.matches_prefix(&prefix));
+        assert!(!elem.matches_prefix(&NetworkPrefix::new(prefix.prefix, 1)));
+        assert!(!elem.has_community(&no_export));
+        assert!(!elem.has_origin_asn(15169.into()));
+        assert!(!elem.path_contains(3356.into()));
+
+        elem.communities = Some(vec![no_export]);
+        elem.as_path = Some(AsPath::from_str("65000 3356 15169").unwrap());
+        elem.origin_asns = Some(Asn::from(15169).into());
+        assert!(elem.has_community(&no_export));
+        assert!(!elem.has_community(&MetaCommunity::Community(Community::NoAdvertise)));
+        assert!(elem.has_origin_asn(15169.into()));
+        assert!(!elem.has_origin_asn(3356.into()));
+        assert!(elem.path_contains(3356.into()));
+        assert!(!elem.path_contains(174.into()));
+    }
+
+    #[test]
+    fn test_path_intersects() {
+        let targets: HashSet<Asn> = [174, 3356].map(|i|{i.into()}).to_vec().into_iter().collect();
+        let mut elem = BgpElem::default();
+        assert!(!elem.path_intersects(&targets));
+
+        elem.as_path = Some(AsPath::from_str("65000 3356 15169").unwrap());
+        assert!(elem.path_intersects(&targets));
+
+        elem.as_path = Some(AsPath::from_str("65000 6939 {15169,13335}").unwrap());
+        assert!(!elem.path_intersects(&targets));
+    }
+
+    #[test]
+    fn test_bogon_origin() {
+        let mut elem = BgpElem::default();
+        assert_eq!(elem.has_bogon_origin(), None);
+
+        elem.origin_asns = Some(Asn::from(64512).into());
+        assert_eq!(elem.has_bogon_origin(), Some(true));
+
+        elem.origin_asns = Some(Asn::from(13335).into());
+        assert_eq!(elem.has_bogon_origin(), Some(false));
+    }
+
+    #[test]
+    fn test_builder() {
+        let elem = BgpElemBuilder::new()
+            .timestamp(1.0)
+            .elem_type(ElemType::ANNOUNCE)
+            .peer_ip(IpAddr::from_str("192.168.1.1").unwrap())
+            .peer_asn(65000.into())
+            .prefix(NetworkPrefix::from_str("8.8.8.0/24").unwrap())
+            .next_hop(IpAddr::from_str("192.168.1.2").unwrap())
+            .origin(Origin::IGP)
+            .communities(vec![MetaCommunity::Community(Community::NoExport)])
+            .origin_validation(RpkiValidationState::NotFound)
+            .aspa_validation(AspaValidationState::Valid)
+            .build()
+            .unwrap();
+        assert_eq!(elem, BgpElem {
+            timestamp: 1.0,
+            elem_type: ElemType::ANNOUNCE,
+            peer_ip: IpAddr::from_str("192.168.1.1").unwrap(),
+            peer_asn: 65000.into(),
+            prefix: NetworkPrefix::from_str("8.8.8.0/24").unwrap(),
+            next_hop: Some(IpAddr::from_str("192.168.1.2").unwrap()),
+            origin: Some(Origin::IGP),
+            communities: Some(vec![MetaCommunity::Community(Community::NoExport)]),
+            origin_validation: Some(RpkiValidationState::NotFound),
+            aspa_validation: Some(AspaValidationState::Valid),
+            ..Default::default()
+        });
+
+        let res = BgpElemBuilder::new()
+            .timestamp(1.0)
+            .peer_ip(IpAddr::from_str("192.168.1.1").unwrap())
+            .peer_asn(65000.into())
+            .build();
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_coalesced_elems() {
+        let as_path = AsPath::from_segments(vec![
+            crate::bgp::AsPathSegment::AsSequence([1,2,3].map(|i|{i.into()}).to_vec()),
+        ]);
+        let elem1 = BgpElem {
+            peer_ip: IpAddr::from_str("192.168.1.1").unwrap(),
+            prefix: NetworkPrefix::from_str("8.8.8.0/24").unwrap(),
+            as_path: Some(as_path.clone()),
+            ..Default::default()
+        };
+        let elem2 = BgpElem {
+            prefix: NetworkPrefix::from_str("1.1.1.0/24").unwrap(),
+            ..elem1.clone()
+        };
+        let elem3 = BgpElem {
+            peer_ip: IpAddr::from_str("192.168.1.2").unwrap(),
+            ..elem1.clone()
+        };
+
+        let elems = vec![elem1.clone(), elem3.clone(), elem2.clone()];
+        let coalesced: CoalescedElems = elems.into_iter().collect();
+        assert_eq!(coalesced.groups().len(), 2);
+        assert_eq!(coalesced.groups()[0].prefixes, vec![elem1.prefix, elem2.prefix]);
+        assert_eq!(coalesced.len(), 3);
+        assert_eq!(coalesced.iter().collect::<Vec<BgpElem>>(), vec![elem1, elem2, elem3]);
+    }
+
+    #[test]
+    fn test_coalesced_elems_validation_states() {
+        let valid = BgpElem {
+            prefix: NetworkPrefix::from_str("8.8.8.0/24").unwrap(),
+            origin_validation: Some(RpkiValidationState::Valid),
+            ..Default::default()
+        };
+        let invalid = BgpElem {
+            prefix: NetworkPrefix::from_str("1.1.1.0/24").unwrap(),
+            origin_validation: Some(RpkiValidationState::Invalid),
+            ..valid.clone()
+        };
+        let coalesced: CoalescedElems = vec![valid.clone(), invalid.clone()].into_iter().collect();
+        assert_eq!(coalesced.groups().len(), 2);
+        assert_eq!(coalesced.iter().collect::<Vec<BgpElem>>(), vec![valid, invalid]);
+    }
+
+    #[test]
+    fn test_elem_stats() {
+        let elem1 = BgpElem {
+            peer_ip: IpAddr::from_str("192.168.1.1").unwrap(),
+            peer_asn: 65000.into(),
+            prefix: NetworkPrefix::from_str("8.8.8.0/24").unwrap(),
+            origin_asns: Some(OriginAsns::Single(15169.into())),
+            ..Default::default()
+        };
+        let elem2 = BgpElem {
+            prefix: NetworkPrefix::from_str("1.1.1.0/24").unwrap(),
+            origin_asns: Some(OriginAsns::Multiple([13335, 15169].map(|i|{i.into()}).to_vec())),
+            ..elem1.clone()
+        };
+        let elem3 = BgpElem {
+            peer_ip: IpAddr::from_str("192.168.1.2").unwrap(),
+            ..elem1.clone()
+        };
+        let elem4 = BgpElem {
+            elem_type: ElemType::WITHDRAW,
+            origin_asns: None,
+            ..elem2.clone()
+        };
+
+        let mut stats = ElemStats::new();
+        for elem in [elem1, elem2, elem3, elem4].iter() {
+            stats.observe(elem);
+        }
+        assert_eq!(stats.announcements, 3);
+        assert_eq!(stats.withdrawals, 1);
+        assert_eq!(stats.prefixes.len(), 2);
+        assert_eq!(stats.peers.len(), 2);
+        assert_eq!(stats.origin_announcements.get(&Asn::from(15169)), Some(&3));
+        assert_eq!(stats.origin_announcements.get(&Asn::from(13335)), Some(&1));
+        assert_eq!(stats.summary(), "3 announcements, 1 withdrawals, 2 prefixes, 2 peers, 2 origins");
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let elem = BgpElem {
+            timestamp: 1.5,
+            elem_type: ElemType::ANNOUNCE,
+            peer_ip: IpAddr::from_str("192.168.1.1").unwrap(),
+            peer_asn: 65000.into(),
+            prefix: NetworkPrefix::from_str("8.8.8.0/24").unwrap(),
+            next_hop: Some(IpAddr::from_str("192.168.1.2").unwrap()),
+            as_path: Some(AsPath::from_str("65000 3356 {15169,13335}").unwrap()),
+            origin_asns: Some(vec![15169.into(), 13335.into()].into()),
+            origin: Some(Origin::IGP),
+            local_pref: Some(100),
+            med: Some(0),
+            communities: Some(vec![
+                MetaCommunity::Community(Community::Custom(65000.into(), 1)),
+                MetaCommunity::LargeCommunity(LargeCommunity::new(65000, [1, 2])),
+            ]),
+            atomic: Some(AtomicAggregate::AG),
+            aggr_asn: Some(15169.into()),
+            aggr_ip: Some(IpAddr::from_str("10.0.0.1").unwrap()),
+            cluster_list: Some(vec![IpAddr::from_str("10.0.0.2").unwrap()]),
+            originator_id: Some(IpAddr::from_str("10.0.0.3").unwrap()),
+            origin_validation: Some(RpkiValidationState::Invalid),
+            aspa_validation: Some(AspaValidationState::Unknown),
+        };
+        let json = serde_json::to_string(&elem).unwrap();
+        let parsed: BgpElem = serde_json::from_str(json.as_str()).unwrap();
+        assert_eq!(parsed, elem);
+
+        let elem = BgpElem { elem_type: ElemType::WITHDRAW, ..Default::default() };
+        let json = serde_json::to_string(&elem).unwrap();
+        let parsed: BgpElem = serde_json::from_str(json.as_str()).unwrap();
+        assert_eq!(parsed, elem);
+    }
+
+    #[test]
+    fn test_validation_states() {
+        let mut elem = BgpElem::default();
+        assert_eq!(serde_json::json!(elem)["origin_validation"], serde_json::Value::Null);
+
+        elem.set_origin_validation(RpkiValidationState::Valid);
+        elem.set_aspa_validation(AspaValidationState::Invalid);
+        let json = serde_json::to_value(&elem).unwrap();
+        assert_eq!(json["origin_validation"], "valid");
+        assert_eq!(json["aspa_validation"], "invalid");
+
+        let parsed: BgpElem = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.origin_validation, Some(RpkiValidationState::Valid));
+        assert_eq!(parsed, elem);
+        assert_eq!(BgpElem::from_bytes(&elem.to_bytes()).unwrap(), elem);
+    }
+
+    #[test]
+    fn test_origin_asns() {
+        let single = OriginAsns::from(vec![Asn::from(15169)]);
+        assert!(matches!(single, OriginAsns::Single(_)));
+        assert_eq!(single, OriginAsns::Multiple(vec![15169.into()]));
+        assert_eq!(single.as_slice(), &[Asn::from(15169)]);
+        assert!(single.contains(&15169.into()));
+
+        let multiple = OriginAsns::from(vec![Asn::from(15169), Asn::from(13335)]);
+        assert!(matches!(multiple, OriginAsns::Multiple(_)));
+        assert_eq!(multiple.len(), 2);
+        assert_eq!(multiple.to_vec(), vec![Asn::from(15169), Asn::from(13335)]);
+
+        // the single-origin case adds no size over a Vec and does not allocate
+        assert!(std::mem::size_of::<OriginAsns>() <= std::mem::size_of::<Vec<Asn>>());
+        assert_eq!(std::mem::size_of::<Option<OriginAsns>>(), std::mem::size_of::<Option<Vec<Asn>>>());
+
+        assert_eq!(serde_json::json!(single), serde_json::json!([15169]));
+        assert_eq!(serde_json::json!(multiple), serde_json::json!([15169, 13335]));
+        let parsed: OriginAsns = serde_json::from_str("[15169]").unwrap();
+        assert!(matches!(parsed, OriginAsns::Single(_)));
+    }
+
+    #[test]
+    fn test_elem_type_code() {
+        for t in [ElemType::ANNOUNCE, ElemType::WITHDRAW] {
+            assert_eq!(ElemType::from_code(t.code()), Some(t));
+        }
+        assert_eq!(ElemType::ANNOUNCE.code(), 0);
+        assert_eq!(ElemType::WITHDRAW.code(), 1);
+        assert_eq!(ElemType::from_code(2), None);
+    }
 }
\ No newline at end of file
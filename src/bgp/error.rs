@@ -6,6 +6,7 @@ use std::error::Error;
 use std::fmt::{Display, Formatter};
 use serde::Serialize;
 use num_traits::FromPrimitive;
+use crate::bgp::AttrType;
 
 /// Error for parsing BGP error code
 #[derive(Debug, PartialEq, Eq, Clone, Serialize)]
@@ -93,14 +94,7 @@ pub fn parse_error_codes(error_code: &u8, error_subcode: &u8) -> Result<BgpError
             }
         },
         6 => {
-            match BgpCeaseNotificationMessageSubcode::from_u8(*error_subcode){
-                Some(v) => {
-                    Ok(BgpError::BgpCeaseNotification(v))
-                },
-                None => {
-                    Err(BgpErrorCodeParsingError::UnknownSubcode(*error_subcode))
-                }
-            }
+            Ok(BgpError::BgpCeaseNotification(BgpCeaseNotificationMessageSubcode::from(*error_subcode)))
         },
         7 => {
             match BgpRouteRefreshMessageErrorSubcode::from_u8(*error_subcode){
@@ -132,6 +126,100 @@ pub enum BgpError {
     BgpRouteFreshMessageError(BgpRouteRefreshMessageErrorSubcode),
 }
 
+/// Display the error name, followed by the subcode name if the error has subcodes, e.g.
+/// `Cease/ADMINISTRATIVE_SHUTDOWN`.
+impl Display for BgpError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BgpError::Reserved => write!(f, "Reserved"),
+            BgpError::MessageHeaderError(v) => write!(f, "Message Header Error/{:?}", v),
+            BgpError::OpenMessageError(v) => write!(f, "OPEN Message Error/{:?}", v),
+            BgpError::UpdateMessageError(v) => write!(f, "UPDATE Message Error/{:?}", v),
+            BgpError::HoldTimerExpired => write!(f, "Hold Timer Expired"),
+            BgpError::BgpFiniteStateMachineError(v) => write!(f, "Finite State Machine Error/{:?}", v),
+            BgpError::BgpCeaseNotification(BgpCeaseNotificationMessageSubcode::Unknown(v)) => write!(f, "Cease/{}", v),
+            BgpError::BgpCeaseNotification(v) => write!(f, "Cease/{:?}", v),
+            BgpError::BgpRouteFreshMessageError(v) => write!(f, "ROUTE-REFRESH Message Error/{:?}", v),
+        }
+    }
+}
+
+/// Structured content of the data field of a NOTIFICATION message.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc4271#section-6>
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Serialize)]
+pub enum NotificationDetail {
+    /// The erroneous message length, for Bad Message Length.
+    BadMessageLength(u16),
+    /// The erroneous message type, for Bad Message Type.
+    BadMessageType(u8),
+    /// The largest locally-supported version number, for Unsupported Version Number.
+    UnsupportedVersion(u16),
+}
+
+impl BgpError {
+    /// Parse the data field of a NOTIFICATION message carrying this error.
+    ///
+    /// Returns `None` for errors that define no structured data, or if the data has an unexpected
+    /// length. The data of other errors, such as the erroneous attribute of UPDATE errors, is left
+    /// to the caller.
+    pub fn parse_data(&self, data: &[u8]) -> Option<NotificationDetail> {
+        match (self, data) {
+            (BgpError::MessageHeaderError(MessageHeaderErrorSubcode::BAD_MESSAGE_LENGTH), [a, b]) => {
+                Some(NotificationDetail::BadMessageLength(u16::from_be_bytes([*a, *b])))
+            }
+            (BgpError::MessageHeaderError(MessageHeaderErrorSubcode::BAD_MESSAGE_TYPE), [t]) => {
+                Some(NotificationDetail::BadMessageType(*t))
+            }
+            (BgpError::OpenMessageError(OpenMessageErrorSubcode::UNSUPPORTED_VERSION_NUMBER), [a, b]) => {
+                Some(NotificationDetail::UnsupportedVersion(u16::from_be_bytes([*a, *b])))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Error found by [BgpUpdateMessage::validate](crate::bgp::BgpUpdateMessage::validate).
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Serialize)]
+pub enum UpdateValidationError {
+    /// A mandatory well-known attribute is missing from an announcement.
+    MissingWellKnownAttribute(AttrType),
+    /// The attribute flags conflict with the attribute type.
+    AttributeFlagsError(AttrType),
+    /// The attribute appears more than once.
+    DuplicateAttribute(AttrType),
+}
+
+impl UpdateValidationError {
+    /// Get the UPDATE Message Error subcode to report the error with
+    /// ([RFC 4271 section 6.3](https://datatracker.ietf.org/doc/html/rfc4271#section-6.3)).
+    pub fn subcode(&self) -> UpdateMessageErrorSubcode {
+        match self {
+            UpdateValidationError::MissingWellKnownAttribute(_) => UpdateMessageErrorSubcode::MISSING_WELL_KNOWN_ATTRIBUTE,
+            UpdateValidationError::AttributeFlagsError(_) => UpdateMessageErrorSubcode::ATTRIBUTE_FLAGS_ERROR,
+            UpdateValidationError::DuplicateAttribute(_) => UpdateMessageErrorSubcode::MALFORMED_ATTRIBUTE_LIST,
+        }
+    }
+}
+
+impl Display for UpdateValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UpdateValidationError::MissingWellKnownAttribute(v) => {
+                write!(f, "missing well-known attribute {:?}", v)
+            }
+            UpdateValidationError::AttributeFlagsError(v) => {
+                write!(f, "attribute flags error for {:?}", v)
+            }
+            UpdateValidationError::DuplicateAttribute(v) => {
+                write!(f, "duplicate attribute {:?}", v)
+            }
+        }
+    }
+}
+
+impl Error for UpdateValidationError{}
+
 /// Message Header Error subcodes
 ///
 /// <https://www.iana.org/assignments/bgp-parameters/bgp-parameters.xhtml#bgp-parameters-5>
@@ -215,21 +303,63 @@ pub enum BgpFiniteStateMachineErrorSubcode {
 /// <https://www.iana.org/assignments/bgp-parameters/bgp-parameters.xhtml#bgp-parameters-8>
 ///
 /// *See source code for number assignment*
+///
+/// Subcodes without an assignment are kept as `Unknown`, so that newly registered subcodes are
+/// not lost. Use `From<u8>` and `From<BgpCeaseNotificationMessageSubcode> for u8` to convert
+/// from and to the numeric subcode.
 #[allow(non_camel_case_types)]
-#[derive(Debug, Primitive, PartialEq, Eq, Hash, Copy, Clone, Serialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Serialize)]
 pub enum BgpCeaseNotificationMessageSubcode {
-    RESERVED = 0,
-    MAXIMUM_NUMBER_OF_PREFIXES_REACHED = 1,
-    ADMINISTRATIVE_SHUTDOWN = 2,
-    PEER_DE_CONFIGURED = 3,
-    ADMINISTRATIVE_RESET = 4,
-    CONNECTION_REJECTED = 5,
-    OTHER_CONFIGURATION_CHANGE = 6,
-    CONNECTION_COLLISION_RESOLUTION = 7,
-    OUT_OF_RESOURCES = 8,
-    HARD_RESET = 9,
-    BFD_DOWN = 10, // TEMPORARY - registered 2022-02-23, expires 2023-02-23
-    // 11 - 255: unassigned
+    RESERVED, // 0
+    MAXIMUM_NUMBER_OF_PREFIXES_REACHED, // 1
+    ADMINISTRATIVE_SHUTDOWN, // 2
+    PEER_DE_CONFIGURED, // 3
+    ADMINISTRATIVE_RESET, // 4
+    CONNECTION_REJECTED, // 5
+    OTHER_CONFIGURATION_CHANGE, // 6
+    CONNECTION_COLLISION_RESOLUTION, // 7
+    OUT_OF_RESOURCES, // 8
+    HARD_RESET, // 9
+    BFD_DOWN, // 10, TEMPORARY - registered 2022-02-23, expires 2023-02-23
+    Unknown(u8), // 11 - 255: unassigned
+}
+
+impl From<u8> for BgpCeaseNotificationMessageSubcode {
+    fn from(v: u8) -> Self {
+        match v {
+            0 => BgpCeaseNotificationMessageSubcode::RESERVED,
+            1 => BgpCeaseNotificationMessageSubcode::MAXIMUM_NUMBER_OF_PREFIXES_REACHED,
+            2 => BgpCeaseNotificationMessageSubcode::ADMINISTRATIVE_SHUTDOWN,
+            3 => BgpCeaseNotificationMessageSubcode::PEER_DE_CONFIGURED,
+            4 => BgpCeaseNotificationMessageSubcode::ADMINISTRATIVE_RESET,
+            5 => BgpCeaseNotificationMessageSubcode::CONNECTION_REJECTED,
+            6 => BgpCeaseNotificationMessageSubcode::OTHER_CONFIGURATION_CHANGE,
+            7 => BgpCeaseNotificationMessageSubcode::CONNECTION_COLLISION_RESOLUTION,
+            8 => BgpCeaseNotificationMessageSubcode::OUT_OF_RESOURCES,
+            9 => BgpCeaseNotificationMessageSubcode::HARD_RESET,
+            10 => BgpCeaseNotificationMessageSubcode::BFD_DOWN,
+            v => BgpCeaseNotificationMessageSubcode::Unknown(v),
+        }
+    }
+}
+
+impl From<BgpCeaseNotificationMessageSubcode> for u8 {
+    fn from(v: BgpCeaseNotificationMessageSubcode) -> Self {
+        match v {
+            BgpCeaseNotificationMessageSubcode::RESERVED => 0,
+            BgpCeaseNotificationMessageSubcode::MAXIMUM_NUMBER_OF_PREFIXES_REACHED => 1,
+            BgpCeaseNotificationMessageSubcode::ADMINISTRATIVE_SHUTDOWN => 2,
+            BgpCeaseNotificationMessageSubcode::PEER_DE_CONFIGURED => 3,
+            BgpCeaseNotificationMessageSubcode::ADMINISTRATIVE_RESET => 4,
+            BgpCeaseNotificationMessageSubcode::CONNECTION_REJECTED => 5,
+            BgpCeaseNotificationMessageSubcode::OTHER_CONFIGURATION_CHANGE => 6,
+            BgpCeaseNotificationMessageSubcode::CONNECTION_COLLISION_RESOLUTION => 7,
+            BgpCeaseNotificationMessageSubcode::OUT_OF_RESOURCES => 8,
+            BgpCeaseNotificationMessageSubcode::HARD_RESET => 9,
+            BgpCeaseNotificationMessageSubcode::BFD_DOWN => 10,
+            BgpCeaseNotificationMessageSubcode::Unknown(v) => v,
+        }
+    }
 }
 
 /// BGP ROUTE-REFRESH Message Error subcodes
@@ -249,6 +379,20 @@ pub enum BgpRouteRefreshMessageErrorSubcode {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_data() {
+        let error = parse_error_codes(&2, &1).unwrap();
+        assert_eq!(error.parse_data(&[0, 4]), Some(NotificationDetail::UnsupportedVersion(4)));
+        assert_eq!(error.parse_data(&[4]), None);
+
+        let error = parse_error_codes(&1, &2).unwrap();
+        assert_eq!(error.parse_data(&[0x10, 0x01]), Some(NotificationDetail::BadMessageLength(4097)));
+        let error = parse_error_codes(&1, &3).unwrap();
+        assert_eq!(error.parse_data(&[5]), Some(NotificationDetail::BadMessageType(5)));
+
+        assert_eq!(BgpError::HoldTimerExpired.parse_data(&[]), None);
+    }
+
     #[test]
     fn test_parsing() {
         let mut error_code: u8;
@@ -368,7 +512,9 @@ mod tests {
         error_subcode = 10;
         assert_eq!(parse_error_codes(&error_code, &error_subcode), Ok(BgpError::BgpCeaseNotification(BgpCeaseNotificationMessageSubcode::BFD_DOWN)));
         error_subcode = 11;
-        assert_eq!(parse_error_codes(&error_code, &error_subcode), Err(BgpErrorCodeParsingError::UnknownSubcode(11)));
+        assert_eq!(parse_error_codes(&error_code, &error_subcode), Ok(BgpError::BgpCeaseNotification(BgpCeaseNotificationMessageSubcode::Unknown(11))));
+        assert_eq!(u8::from(BgpCeaseNotificationMessageSubcode::Unknown(11)), 11);
+        assert_eq!(u8::from(BgpCeaseNotificationMessageSubcode::BFD_DOWN), 10);
 
         error_code = 7;
         error_subcode = 0;
@@ -0,0 +1,231 @@
+//! BGP Flow Specification structs
+//!
+//! Flow specification rules are carried in MP_REACH_NLRI and MP_UNREACH_NLRI attributes with
+//! SAFI 133 ([Safi::Flowspec](crate::network::Safi::Flowspec)) and 134
+//! ([Safi::FlowspecVpn](crate::network::Safi::FlowspecVpn)), see
+//! [RFC 8955](https://datatracker.ietf.org/doc/html/rfc8955).
+use std::fmt::{Display, Formatter};
+use itertools::Itertools;
+use serde::Serialize;
+use crate::network::NetworkPrefix;
+
+/// Flow specification NLRI, i.e. one traffic filtering rule.
+///
+/// A packet matches the rule if it matches all components.
+#[derive(Debug, PartialEq, Clone, Serialize, Eq, Hash)]
+pub struct FlowspecNlri {
+    pub components: Vec<FlowspecComponent>,
+}
+
+/// Flow specification component.
+///
+/// <https://datatracker.ietf.org/doc/html/rfc8955#section-4.2.2>
+#[derive(Debug, PartialEq, Clone, Serialize, Eq, Hash)]
+pub enum FlowspecComponent {
+    DestinationPrefix(NetworkPrefix),
+    SourcePrefix(NetworkPrefix),
+    IpProtocol(Vec<NumericOperation>),
+    Port(Vec<NumericOperation>),
+    DestinationPort(Vec<NumericOperation>),
+    SourcePort(Vec<NumericOperation>),
+    IcmpType(Vec<NumericOperation>),
+    IcmpCode(Vec<NumericOperation>),
+    TcpFlags(Vec<BitmaskOperation>),
+    PacketLength(Vec<NumericOperation>),
+    Dscp(Vec<NumericOperation>),
+    Fragment(Vec<BitmaskOperation>),
+}
+
+impl FlowspecComponent {
+    /// Get the component type code.
+    pub fn type_code(&self) -> u8 {
+        match self {
+            FlowspecComponent::DestinationPrefix(_) => 1,
+            FlowspecComponent::SourcePrefix(_) => 2,
+            FlowspecComponent::IpProtocol(_) => 3,
+            FlowspecComponent::Port(_) => 4,
+            FlowspecComponent::DestinationPort(_) => 5,
+            FlowspecComponent::SourcePort(_) => 6,
+            FlowspecComponent::IcmpType(_) => 7,
+            FlowspecComponent::IcmpCode(_) => 8,
+            FlowspecComponent::TcpFlags(_) => 9,
+            FlowspecComponent::PacketLength(_) => 10,
+            FlowspecComponent::Dscp(_) => 11,
+            FlowspecComponent::Fragment(_) => 12,
+        }
+    }
+}
+
+/// Numeric operator and value of a component.
+///
+/// ```text
+///    0   1   2   3   4   5   6   7
+///  +---+---+---+---+---+---+---+---+
+///  | e | a |  len  | 0 |lt |gt |eq |
+///  +---+---+---+---+---+---+---+---+
+/// ```
+///
+/// `and` combines the operation with the previous one by logical AND instead of OR. The
+/// comparison matches if any of the set `lt`/`gt`/`eq` relations between the data and the value
+/// holds. See <https://datatracker.ietf.org/doc/html/rfc8955#section-4.2.1.1>.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Eq, Hash)]
+pub struct NumericOperation {
+    pub and: bool,
+    pub lt: bool,
+    pub gt: bool,
+    pub eq: bool,
+    pub value: u64,
+}
+
+/// Bitmask operator and value of a component.
+///
+/// ```text
+///    0   1   2   3   4   5   6   7
+///  +---+---+---+---+---+---+---+---+
+///  | e | a |  len  | 0 | 0 |not| m |
+///  +---+---+---+---+---+---+---+---+
+/// ```
+///
+/// `and` combines the operation with the previous one by logical AND instead of OR. With
+/// `matches` set, all bits of the value must be set in the data, otherwise any of them. `not`
+/// negates the result. See <https://datatracker.ietf.org/doc/html/rfc8955#section-4.2.1.2>.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Eq, Hash)]
+pub struct BitmaskOperation {
+    pub and: bool,
+    pub not: bool,
+    pub matches: bool,
+    pub value: u64,
+}
+
+/////////////
+// DISPLAY //
+/////////////
+
+fn operations_to_string<T: Display>(ops: &[T], and: impl Fn(&T) -> bool) -> String {
+    ops.iter().enumerate().map(|(i, op)| match (i, and(op)) {
+        (0, _) => op.to_string(),
+        (_, true) => format!("&&{}", op),
+        (_, false) => format!("||{}", op),
+    }).join("")
+}
+
+/// Display the comparison and the value, e.g. `>=1024`.
+impl Display for NumericOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let op = match (self.lt, self.gt, self.eq) {
+            (false, false, false) => "false",
+            (false, false, true) => "=",
+            (false, true, false) => ">",
+            (false, true, true) => ">=",
+            (true, false, false) => "<",
+            (true, false, true) => "<=",
+            (true, true, false) => "!=",
+            (true, true, true) => "true",
+        };
+        match (self.lt, self.gt, self.eq) {
+            (false, false, false) | (true, true, true) => write!(f, "{}", op),
+            _ => write!(f, "{}{}", op, self.value),
+        }
+    }
+}
+
+/// Display the operator and the value in hex, e.g. `=0x02` for a match or `!0x02` for none of
+/// the bits set.
+impl Display for BitmaskOperation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let op = match (self.not, self.matches) {
+            (false, false) => "",
+            (false, true) => "=",
+            (true, false) => "!",
+            (true, true) => "!=",
+        };
+        write!(f, "{}{:#04x}", op, self.value)
+    }
+}
+
+impl Display for FlowspecComponent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let numeric = |ops: &[NumericOperation]| operations_to_string(ops, |op| op.and);
+        let bitmask = |ops: &[BitmaskOperation]| operations_to_string(ops, |op| op.and);
+        match self {
+            FlowspecComponent::DestinationPrefix(v) => write!(f, "dst {}", v),
+            FlowspecComponent::SourcePrefix(v) => write!(f, "src {}", v),
+            FlowspecComponent::IpProtocol(v) => write!(f, "proto {}", numeric(v)),
+            FlowspecComponent::Port(v) => write!(f, "port {}", numeric(v)),
+            FlowspecComponent::DestinationPort(v) => write!(f, "dst-port {}", numeric(v)),
+            FlowspecComponent::SourcePort(v) => write!(f, "src-port {}", numeric(v)),
+            FlowspecComponent::IcmpType(v) => write!(f, "icmp-type {}", numeric(v)),
+            FlowspecComponent::IcmpCode(v) => write!(f, "icmp-code {}", numeric(v)),
+            FlowspecComponent::TcpFlags(v) => write!(f, "tcp-flags {}", bitmask(v)),
+            FlowspecComponent::PacketLength(v) => write!(f, "pkt-len {}", numeric(v)),
+            FlowspecComponent::Dscp(v) => write!(f, "dscp {}", numeric(v)),
+            FlowspecComponent::Fragment(v) => write!(f, "fragment {}", bitmask(v)),
+        }
+    }
+}
+
+/// Display the components of the rule separated by spaces, e.g. `dst 10.0.0.0/24 proto =6`.
+impl Display for FlowspecNlri {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.components.iter().join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::bgp::{AttrType, Attribute, AttributeValue, BgpMessage, BgpUpdateMessage, Nlri};
+    use crate::network::{Afi, Safi};
+    use super::*;
+
+    #[test]
+    fn test_flowspec_display() {
+        let eq = |value: u64| NumericOperation { and: false, lt: false, gt: false, eq: true, value };
+        let rule = FlowspecNlri {
+            components: vec![
+                FlowspecComponent::DestinationPrefix(NetworkPrefix::from_str("10.0.0.0/24").unwrap()),
+                FlowspecComponent::IpProtocol(vec![eq(6)]),
+            ],
+        };
+        assert_eq!(rule.components.iter().map(|c| c.type_code()).collect::<Vec<u8>>(), vec![1, 3]);
+        assert_eq!(rule.to_string(), "dst 10.0.0.0/24 proto =6");
+
+        let ports = FlowspecComponent::DestinationPort(vec![
+            eq(80),
+            eq(443),
+            NumericOperation { and: false, lt: false, gt: true, eq: true, value: 1024 },
+            NumericOperation { and: true, lt: true, gt: false, eq: true, value: 2048 },
+        ]);
+        assert_eq!(ports.to_string(), "dst-port =80||=443||>=1024&&<=2048");
+
+        let syn = FlowspecComponent::TcpFlags(vec![BitmaskOperation { and: false, not: false, matches: true, value: 0x02 }]);
+        assert_eq!(syn.to_string(), "tcp-flags =0x02");
+    }
+
+    #[test]
+    fn test_flowspec_update_display() {
+        let rule = FlowspecNlri {
+            components: vec![
+                FlowspecComponent::DestinationPrefix(NetworkPrefix::from_str("192.0.2.0/24").unwrap()),
+                FlowspecComponent::IpProtocol(vec![NumericOperation { and: false, lt: false, gt: false, eq: true, value: 17 }]),
+            ],
+        };
+        let msg = BgpMessage::Update(BgpUpdateMessage {
+            withdrawn_prefixes: vec![],
+            attributes: vec![Attribute {
+                attr_type: AttrType::MP_REACHABLE_NLRI,
+                value: AttributeValue::MpReachNlri(Nlri {
+                    afi: Afi::Ipv4,
+                    safi: Safi::Flowspec,
+                    next_hop: None,
+                    prefixes: vec![],
+                    flowspec: vec![rule],
+                }),
+                flag: 0x80,
+            }],
+            announced_prefixes: vec![],
+        });
+        // flowspec rules are not counted as announced prefixes
+        assert_eq!(msg.to_string(), "UPDATE: 0 announced, 0 withdrawn");
+    }
+}
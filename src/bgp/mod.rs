@@ -6,6 +6,7 @@ pub mod community;
 pub mod error;
 pub mod capabilities;
 pub mod role;
+pub mod flowspec;
 
 pub use crate::bgp::attributes::*;
 pub use crate::bgp::elem::*;
@@ -13,11 +14,13 @@ pub use crate::bgp::community::*;
 pub use crate::bgp::error::*;
 pub use crate::bgp::capabilities::*;
 pub use crate::bgp::role::*;
+pub use crate::bgp::flowspec::*;
 
 use serde::Serialize;
+use num_traits::FromPrimitive;
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
 use std::net::Ipv4Addr;
-use crate::bgp::capabilities::BgpCapabilityType;
-use crate::bgp::error::BgpError;
 use crate::network::*;
 
 #[derive(Debug, Primitive, Copy, Clone, Serialize, PartialEq)]
@@ -29,7 +32,7 @@ pub enum BgpMessageType {
 }
 
 // https://tools.ietf.org/html/rfc4271#section-4
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub enum BgpMessage{
     Open(BgpOpenMessage),
     Update(BgpUpdateMessage),
@@ -37,6 +40,33 @@ pub enum BgpMessage{
     KeepAlive(BgpKeepAliveMessage),
 }
 
+/// Display a one-line summary of the message, e.g. `UPDATE: 2 announced, 1 withdrawn`.
+///
+/// UPDATE counts include the prefixes of MP_REACH_NLRI and MP_UNREACH_NLRI attributes.
+impl Display for BgpMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BgpMessage::Open(msg) => {
+                write!(f, "OPEN: version {}, AS {}, hold time {}", msg.version, msg.asn, msg.hold_time)
+            }
+            BgpMessage::Update(msg) => {
+                let mut announced = msg.announced_prefixes.len();
+                let mut withdrawn = msg.withdrawn_prefixes.len();
+                for attr in &msg.attributes {
+                    match &attr.value {
+                        AttributeValue::MpReachNlri(nlri) => announced += nlri.prefixes.len(),
+                        AttributeValue::MpUnreachNlri(nlri) => withdrawn += nlri.prefixes.len(),
+                        _ => {}
+                    }
+                }
+                write!(f, "UPDATE: {} announced, {} withdrawn", announced, withdrawn)
+            }
+            BgpMessage::Notification(msg) => write!(f, "NOTIFICATION: {}", msg),
+            BgpMessage::KeepAlive(_) => write!(f, "KEEPALIVE"),
+        }
+    }
+}
+
 /// BGP Open Message
 ///
 /// ```text
@@ -58,7 +88,7 @@ pub enum BgpMessage{
 ///  |                                                               |
 ///  +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
 /// ```
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct BgpOpenMessage {
     pub version: u8,
     pub asn: Asn,
@@ -68,24 +98,94 @@ pub struct BgpOpenMessage {
     pub opt_params: Vec<OptParam>
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct OptParam {
     pub param_type: u8,
     pub param_len: u16,
     pub param_value: ParamValue,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub enum ParamValue {
     Raw(Vec<u8>),
     Capability(Capability)
 }
 
+/// Optional parameter types.
+///
+/// <https://www.iana.org/assignments/bgp-parameters/bgp-parameters.xhtml#bgp-parameters-11>
+#[derive(Debug, Primitive, Copy, Clone, Serialize, PartialEq, Eq)]
+pub enum OptParamType {
+    /// Authentication, deprecated by [RFC 5492](https://datatracker.ietf.org/doc/html/rfc5492).
+    Authentication = 1,
+    /// Capabilities ([RFC 5492](https://datatracker.ietf.org/doc/html/rfc5492#section-4)).
+    Capabilities = 2,
+}
+
+/// Optional parameter value decoded by its type, see [OptParam::decode].
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub enum DecodedOptParam {
+    Authentication(Vec<u8>),
+    Capabilities(Vec<Capability>),
+    /// Value of a parameter of unknown type, or a malformed capabilities parameter.
+    Raw(Vec<u8>),
+}
+
+impl OptParam {
+    /// Get the parameter type, if known.
+    pub fn opt_param_type(&self) -> Option<OptParamType> {
+        OptParamType::from_u8(self.param_type)
+    }
+
+    /// Decode the parameter value by its type.
+    ///
+    /// A single capabilities parameter may carry several capabilities, each encoded as
+    /// `(code: u8, length: u8, value)`. Malformed capabilities values are kept as
+    /// [DecodedOptParam::Raw].
+    pub fn decode(&self) -> DecodedOptParam {
+        let bytes = match &self.param_value {
+            ParamValue::Capability(capability) => {
+                return DecodedOptParam::Capabilities(vec![capability.clone()])
+            }
+            ParamValue::Raw(bytes) => bytes,
+        };
+        match self.opt_param_type() {
+            Some(OptParamType::Authentication) => DecodedOptParam::Authentication(bytes.clone()),
+            Some(OptParamType::Capabilities) => match parse_capabilities(bytes) {
+                Some(capabilities) => DecodedOptParam::Capabilities(capabilities),
+                None => DecodedOptParam::Raw(bytes.clone()),
+            },
+            None => DecodedOptParam::Raw(bytes.clone()),
+        }
+    }
+}
+
+fn parse_capabilities(mut bytes: &[u8]) -> Option<Vec<Capability>> {
+    let mut capabilities = vec![];
+    while let [code, len, rest @ ..] = bytes {
+        if rest.len() < *len as usize {
+            return None
+        }
+        let (value, remaining) = rest.split_at(*len as usize);
+        capabilities.push(Capability {
+            code: *code,
+            len: *len,
+            value: value.to_vec(),
+            capability_type: BgpCapabilityType::from_u8(*code),
+        });
+        bytes = remaining;
+    }
+    match bytes.is_empty() {
+        true => Some(capabilities),
+        false => None,
+    }
+}
+
 /// BGP Capability.
 ///
 /// - RFC3392: <https://datatracker.ietf.org/doc/html/rfc3392>
 /// - Capability codes: <https://www.iana.org/assignments/capability-codes/capability-codes.xhtml#capability-codes-2>
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct Capability {
     pub code: u8,
     pub len: u8,
@@ -93,14 +193,82 @@ pub struct Capability {
     pub capability_type: Option<BgpCapabilityType>,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+impl Capability {
+    /// Decode the capability value into a [DecodedCapability].
+    pub fn decode(&self) -> DecodedCapability {
+        DecodedCapability::decode(self.code, &self.value)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct BgpUpdateMessage {
     pub withdrawn_prefixes: Vec<NetworkPrefix>,
     pub attributes: Vec<Attribute>,
     pub announced_prefixes: Vec<NetworkPrefix>,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+impl BgpUpdateMessage {
+    /// Put the message into a canonical form, so that messages with the same content compare
+    /// and hash equally regardless of the order of attributes.
+    ///
+    /// Attributes are sorted by their type code, and the communities within COMMUNITIES,
+    /// EXTENDED_COMMUNITIES and LARGE_COMMUNITIES attributes are sorted and deduplicated.
+    pub fn canonicalize(&mut self) {
+        self.attributes.sort_by_key(|attr| attr.attr_type as u8);
+        for attr in self.attributes.iter_mut() {
+            match &mut attr.value {
+                AttributeValue::Communities(v) => {
+                    v.sort();
+                    v.dedup();
+                }
+                AttributeValue::ExtendedCommunities(v) => {
+                    v.sort();
+                    v.dedup();
+                }
+                AttributeValue::LargeCommunities(v) => {
+                    v.sort();
+                    v.dedup();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Check the attributes of the message for errors that make it malformed.
+    ///
+    /// - Each attribute type must appear at most once.
+    /// - Well-known attributes must have the Optional bit unset and the Transitive bit set, and
+    ///   all other attributes must have the Optional bit set.
+    /// - Messages announcing prefixes must carry ORIGIN and AS_PATH, and NEXT_HOP if IPv4
+    ///   unicast prefixes are announced outside of MP_REACH_NLRI.
+    ///
+    /// The first error found is returned, see [UpdateValidationError::subcode] for the matching
+    /// NOTIFICATION subcode.
+    pub fn validate(&self) -> Result<(), UpdateValidationError> {
+        let mut seen: HashSet<AttrType> = HashSet::new();
+        for attr in &self.attributes {
+            if !seen.insert(attr.attr_type) {
+                return Err(UpdateValidationError::DuplicateAttribute(attr.attr_type))
+            }
+            if !attr.has_valid_flags() {
+                return Err(UpdateValidationError::AttributeFlagsError(attr.attr_type))
+            }
+        }
+
+        let mut mandatory = vec![];
+        if !self.announced_prefixes.is_empty() {
+            mandatory.extend_from_slice(&[AttrType::ORIGIN, AttrType::AS_PATH, AttrType::NEXT_HOP]);
+        } else if seen.contains(&AttrType::MP_REACHABLE_NLRI) {
+            mandatory.extend_from_slice(&[AttrType::ORIGIN, AttrType::AS_PATH]);
+        }
+        match mandatory.into_iter().find(|attr_type| !seen.contains(attr_type)) {
+            Some(attr_type) => Err(UpdateValidationError::MissingWellKnownAttribute(attr_type)),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct BgpNotificationMessage {
     pub error_code: u8,
     pub error_subcode: u8,
@@ -108,8 +276,265 @@ pub struct BgpNotificationMessage {
     pub data: Vec<u8>,
 }
 
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+impl BgpNotificationMessage {
+    /// Decode the raw error code and subcode.
+    ///
+    /// Returns `None` for unassigned or deprecated codes and subcodes, including unassigned
+    /// Cease subcodes.
+    pub fn typed_error(&self) -> Option<BgpError> {
+        match parse_error_codes(&self.error_code, &self.error_subcode) {
+            Ok(BgpError::BgpCeaseNotification(BgpCeaseNotificationMessageSubcode::Unknown(_))) | Err(_) => None,
+            Ok(v) => Some(v),
+        }
+    }
+
+    /// Get the Shutdown Communication of an administrative shutdown or reset CEASE notification
+    /// ([RFC 9003](https://datatracker.ietf.org/doc/html/rfc9003#section-2), updating RFC 8203).
+    ///
+    /// The data holds a length byte followed by the UTF-8 message. Bytes past the declared length
+    /// are ignored. Returns `None` for other notifications, an empty message, data shorter than
+    /// the declared length, or invalid UTF-8.
+    pub fn shutdown_communication(&self) -> Option<String> {
+        match self.typed_error()? {
+            BgpError::BgpCeaseNotification(BgpCeaseNotificationMessageSubcode::ADMINISTRATIVE_SHUTDOWN) |
+            BgpError::BgpCeaseNotification(BgpCeaseNotificationMessageSubcode::ADMINISTRATIVE_RESET) => {}
+            _ => return None,
+        }
+        let (len, rest) = self.data.split_first()?;
+        match rest.get(..*len as usize) {
+            Some(bytes) if !bytes.is_empty() => String::from_utf8(bytes.to_vec()).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Display the decoded error name, e.g. `Cease/ADMINISTRATIVE_SHUTDOWN`, or the raw code and
+/// subcode if they cannot be decoded.
+impl Display for BgpNotificationMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.typed_error() {
+            Some(error) => write!(f, "{}", error),
+            None => write!(f, "error code {} subcode {}", self.error_code, self.error_subcode),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct BgpKeepAliveMessage {
 
 }
 
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+    use std::str::FromStr;
+    use super::*;
+
+    #[test]
+    fn test_capability_decode() {
+        let capability = Capability {
+            code: 65,
+            len: 4,
+            value: vec![0, 0, 0xfd, 0xe8],
+            capability_type: Some(BgpCapabilityType::SUPPORT_FOR_4_OCTET_AS_NUMBER_CAPABILITY),
+        };
+        assert_eq!(capability.decode(), DecodedCapability::FourOctetAsn(65000.into()));
+    }
+
+    #[test]
+    fn test_opt_param_decode() {
+        let param = OptParam {
+            param_type: 2,
+            param_len: 8,
+            param_value: ParamValue::Raw(vec![2, 0, 65, 4, 0, 0, 0xfd, 0xe8]),
+        };
+        assert_eq!(param.opt_param_type(), Some(OptParamType::Capabilities));
+        let capabilities = match param.decode() {
+            DecodedOptParam::Capabilities(v) => v,
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(capabilities.len(), 2);
+        assert_eq!(capabilities[0].decode(), DecodedCapability::RouteRefresh);
+        assert_eq!(capabilities[1].capability_type, Some(BgpCapabilityType::SUPPORT_FOR_4_OCTET_AS_NUMBER_CAPABILITY));
+        assert_eq!(capabilities[1].decode(), DecodedCapability::FourOctetAsn(65000.into()));
+
+        let truncated = OptParam { param_type: 2, param_len: 3, param_value: ParamValue::Raw(vec![65, 4, 0]) };
+        assert_eq!(truncated.decode(), DecodedOptParam::Raw(vec![65, 4, 0]));
+        let auth = OptParam { param_type: 1, param_len: 1, param_value: ParamValue::Raw(vec![1]) };
+        assert_eq!(auth.decode(), DecodedOptParam::Authentication(vec![1]));
+    }
+
+    #[test]
+    fn test_validate_update() {
+        let origin = Attribute { attr_type: AttrType::ORIGIN, value: AttributeValue::Origin(Origin::IGP), flag: 0x40 };
+        let as_path = Attribute {
+            attr_type: AttrType::AS_PATH,
+            value: AttributeValue::AsPath(AsPath::from_str("65000 3356").unwrap()),
+            flag: 0x40,
+        };
+        let next_hop = Attribute {
+            attr_type: AttrType::NEXT_HOP,
+            value: AttributeValue::NextHop(IpAddr::from_str("10.0.0.1").unwrap()),
+            flag: 0x40,
+        };
+        let med = Attribute { attr_type: AttrType::MULTI_EXIT_DISCRIMINATOR, value: AttributeValue::MultiExitDiscriminator(0), flag: 0x80 };
+        let update = |attributes: Vec<Attribute>| BgpUpdateMessage {
+            withdrawn_prefixes: vec![],
+            attributes,
+            announced_prefixes: vec![NetworkPrefix::from_str("8.8.8.0/24").unwrap()],
+        };
+
+        assert!(update(vec![origin.clone(), as_path.clone(), next_hop.clone(), med.clone()]).validate().is_ok());
+
+        let err = update(vec![as_path.clone(), next_hop.clone()]).validate().unwrap_err();
+        assert_eq!(err, UpdateValidationError::MissingWellKnownAttribute(AttrType::ORIGIN));
+        assert_eq!(err.subcode(), UpdateMessageErrorSubcode::MISSING_WELL_KNOWN_ATTRIBUTE);
+
+        let err = update(vec![origin.clone(), as_path.clone(), next_hop.clone(), as_path.clone()]).validate().unwrap_err();
+        assert_eq!(err, UpdateValidationError::DuplicateAttribute(AttrType::AS_PATH));
+        assert_eq!(err.subcode(), UpdateMessageErrorSubcode::MALFORMED_ATTRIBUTE_LIST);
+
+        let bad_flags = Attribute { flag: 0xc0, ..origin.clone() };
+        let err = update(vec![bad_flags, as_path, next_hop]).validate().unwrap_err();
+        assert_eq!(err, UpdateValidationError::AttributeFlagsError(AttrType::ORIGIN));
+        assert_eq!(err.subcode(), UpdateMessageErrorSubcode::ATTRIBUTE_FLAGS_ERROR);
+
+        let withdrawal = BgpUpdateMessage {
+            withdrawn_prefixes: vec![NetworkPrefix::from_str("8.8.8.0/24").unwrap()],
+            attributes: vec![],
+            announced_prefixes: vec![],
+        };
+        assert!(withdrawal.validate().is_ok());
+    }
+
+    #[test]
+    fn test_notification_typed_error() {
+        let notification = |error_code: u8, error_subcode: u8| BgpNotificationMessage {
+            error_code,
+            error_subcode,
+            error_type: None,
+            data: vec![],
+        };
+
+        let shutdown = notification(6, 2);
+        assert_eq!(
+            shutdown.typed_error(),
+            Some(BgpError::BgpCeaseNotification(BgpCeaseNotificationMessageSubcode::ADMINISTRATIVE_SHUTDOWN))
+        );
+        assert_eq!(shutdown.to_string(), "Cease/ADMINISTRATIVE_SHUTDOWN");
+
+        assert_eq!(notification(4, 0).to_string(), "Hold Timer Expired");
+        assert_eq!(notification(6, 200).typed_error(), None);
+        assert_eq!(notification(6, 200).to_string(), "error code 6 subcode 200");
+        assert_eq!(notification(9, 0).typed_error(), None);
+    }
+
+    #[test]
+    fn test_shutdown_communication() {
+        let notification = |error_subcode: u8, data: Vec<u8>| BgpNotificationMessage {
+            error_code: 6,
+            error_subcode,
+            error_type: None,
+            data,
+        };
+        let message = "maintenance, back in 2h";
+        let mut data = vec![message.len() as u8];
+        data.extend_from_slice(message.as_bytes());
+
+        assert_eq!(notification(2, data.clone()).shutdown_communication(), Some(message.to_string()));
+        assert_eq!(notification(4, data.clone()).shutdown_communication(), Some(message.to_string()));
+        // other subcodes carry no shutdown communication
+        assert_eq!(notification(3, data.clone()).shutdown_communication(), None);
+
+        // trailing bytes are ignored, truncated data is rejected
+        let mut trailing = data.clone();
+        trailing.extend_from_slice(b"xyz");
+        assert_eq!(notification(2, trailing).shutdown_communication(), Some(message.to_string()));
+        assert_eq!(notification(2, data[..10].to_vec()).shutdown_communication(), None);
+
+        assert_eq!(notification(2, vec![2, 0xc3, 0x28]).shutdown_communication(), None);
+        assert_eq!(notification(2, vec![0]).shutdown_communication(), None);
+        assert_eq!(notification(2, vec![]).shutdown_communication(), None);
+    }
+
+    #[test]
+    fn test_bgp_message_display() {
+        let update = BgpMessage::Update(BgpUpdateMessage {
+            withdrawn_prefixes: vec![NetworkPrefix::from_str("10.0.0.0/8").unwrap()],
+            attributes: vec![Attribute {
+                attr_type: AttrType::MP_REACHABLE_NLRI,
+                value: AttributeValue::MpReachNlri(Nlri {
+                    afi: Afi::Ipv6,
+                    safi: Safi::Unicast,
+                    next_hop: None,
+                    prefixes: vec![NetworkPrefix::from_str("2001:db8::/32").unwrap()],
+                    flowspec: vec![],
+                }),
+                flag: 0x80,
+            }],
+            announced_prefixes: vec![
+                NetworkPrefix::from_str("8.8.8.0/24").unwrap(),
+                NetworkPrefix::from_str("1.1.1.0/24").unwrap(),
+            ],
+        });
+        assert_eq!(update.to_string(), "UPDATE: 3 announced, 1 withdrawn");
+
+        let open = BgpMessage::Open(BgpOpenMessage {
+            version: 4,
+            asn: 65000.into(),
+            hold_time: 180,
+            sender_ip: Ipv4Addr::from_str("10.0.0.1").unwrap(),
+            extended_length: false,
+            opt_params: vec![],
+        });
+        assert_eq!(open.to_string(), "OPEN: version 4, AS 65000, hold time 180");
+
+        let notification = BgpMessage::Notification(BgpNotificationMessage {
+            error_code: 6,
+            error_subcode: 2,
+            error_type: None,
+            data: vec![],
+        });
+        assert_eq!(notification.to_string(), "NOTIFICATION: Cease/ADMINISTRATIVE_SHUTDOWN");
+        assert_eq!(BgpMessage::KeepAlive(BgpKeepAliveMessage {}).to_string(), "KEEPALIVE");
+    }
+
+    #[test]
+    fn test_canonicalize_update() {
+        let origin = Attribute {
+            attr_type: AttrType::ORIGIN,
+            value: AttributeValue::Origin(Origin::IGP),
+            flag: 0x40,
+        };
+        let next_hop = Attribute {
+            attr_type: AttrType::NEXT_HOP,
+            value: AttributeValue::NextHop(IpAddr::from_str("10.0.0.1").unwrap()),
+            flag: 0x40,
+        };
+        let communities = |v: Vec<Community>| Attribute {
+            attr_type: AttrType::COMMUNITIES,
+            value: AttributeValue::Communities(v),
+            flag: 0xc0,
+        };
+        let c1 = Community::Custom(65000.into(), 1);
+        let c2 = Community::Custom(65000.into(), 2);
+
+        let mut update1 = BgpUpdateMessage {
+            withdrawn_prefixes: vec![],
+            attributes: vec![communities(vec![c2, c1]), next_hop.clone(), origin.clone()],
+            announced_prefixes: vec![NetworkPrefix::from_str("8.8.8.0/24").unwrap()],
+        };
+        let mut update2 = BgpUpdateMessage {
+            withdrawn_prefixes: vec![],
+            attributes: vec![origin, communities(vec![c1, c2, c1]), next_hop],
+            announced_prefixes: vec![NetworkPrefix::from_str("8.8.8.0/24").unwrap()],
+        };
+        assert_ne!(update1, update2);
+
+        update1.canonicalize();
+        update2.canonicalize();
+        assert_eq!(update1, update2);
+        assert_eq!(update1.attributes[0].attr_type, AttrType::ORIGIN);
+        assert_eq!(update1.attributes[2].value, AttributeValue::Communities(vec![c1, c2]));
+    }
+}
@@ -34,34 +34,19 @@ pub fn parse_bgp_role_value(value: &u8) -> Option<BgpRole> {
 pub fn validate_role_pairs(local_role: &BgpRole, remote_role: &BgpRole) -> bool {
     match local_role {
         BgpRole::Provider => {
-            if let BgpRole::Customer =  remote_role {
-                return true
-            }
-            return false
+            matches!(remote_role, BgpRole::Customer)
         }
         BgpRole::RouteServer => {
-            if let BgpRole::RouteServerClient =  remote_role {
-                return true
-            }
-            return false
+            matches!(remote_role, BgpRole::RouteServerClient)
         }
         BgpRole::RouteServerClient => {
-            if let BgpRole::RouteServer =  remote_role {
-                return true
-            }
-            return false
+            matches!(remote_role, BgpRole::RouteServer)
         }
         BgpRole::Customer => {
-            if let BgpRole::Provider =  remote_role {
-                return true
-            }
-            return false
+            matches!(remote_role, BgpRole::Provider)
         }
         BgpRole::Peer => {
-            if let BgpRole::Peer =  remote_role {
-                return true
-            }
-            return false
+            matches!(remote_role, BgpRole::Peer)
         }
     }
 }
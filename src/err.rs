@@ -5,6 +5,12 @@ use ipnetwork::IpNetworkError;
 #[derive(Debug)]
 pub enum BgpModelsError {
     PrefixParsingError(String),
+    CommunityParsingError(String),
+    SafiParsingError(String),
+    ElemBuildingError(String),
+    AttributeParsingError(String),
+    ElemDecodingError(String),
+    AsnParsingError(String),
 }
 
 impl Display for BgpModelsError {
@@ -13,6 +19,24 @@ impl Display for BgpModelsError {
             BgpModelsError::PrefixParsingError(msg) => {
                 write!(f, "cannot convert str to IP prefix: {}", msg)
             }
+            BgpModelsError::CommunityParsingError(msg) => {
+                write!(f, "cannot parse community: {}", msg)
+            }
+            BgpModelsError::SafiParsingError(msg) => {
+                write!(f, "cannot parse SAFI: {}", msg)
+            }
+            BgpModelsError::ElemBuildingError(msg) => {
+                write!(f, "cannot build BGP element: {}", msg)
+            }
+            BgpModelsError::AttributeParsingError(msg) => {
+                write!(f, "cannot parse attribute: {}", msg)
+            }
+            BgpModelsError::ElemDecodingError(msg) => {
+                write!(f, "cannot decode BGP element: {}", msg)
+            }
+            BgpModelsError::AsnParsingError(msg) => {
+                write!(f, "cannot parse ASN: {}", msg)
+            }
         }
     }
 }
@@ -1,11 +1,15 @@
 //! MRT BGP4MP structs
+use std::fmt::{Display, Formatter};
 use std::net::IpAddr;
 use serde::Serialize;
-use crate::bgp::BgpMessage;
-use crate::network::{Afi, Asn};
+use crate::bgp::{AttributesExt, AttributeValue, BgpElem, BgpMessage, ElemType};
+use crate::network::{Afi, Asn, NetworkPrefix};
 
 /// BGP states enum.
-#[derive(Debug, Primitive, Copy, Clone, Serialize, PartialEq, Eq)]
+///
+/// States are ordered by their numeric code, which reflects how far a connection progressed
+/// towards `Established`. The ordering does not describe which state transitions are legal.
+#[derive(Debug, Primitive, Copy, Clone, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum BgpState {
     Idle = 1,
     Connect = 2,
@@ -15,19 +19,40 @@ pub enum BgpState {
     Established = 6,
 }
 
+/// Display the state name, e.g. `Established`.
+impl Display for BgpState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            BgpState::Idle => "Idle",
+            BgpState::Connect => "Connect",
+            BgpState::Active => "Active",
+            BgpState::OpenSent => "OpenSent",
+            BgpState::OpenConfirm => "OpenConfirm",
+            BgpState::Established => "Established",
+        })
+    }
+}
+
 /// BGP4MP message types.
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+///
+/// The message variants differ in the direction of the recorded message, which determines the
+/// source of the routes it carries (see [Bgp4MpMessage::route_source]).
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub enum Bgp4Mp {
     Bgp4MpStateChange(Bgp4MpStateChange),
     Bgp4MpStateChangeAs4(Bgp4MpStateChange),
+    /// Message received from the peer: the peer is the route source.
     Bgp4MpMessage(Bgp4MpMessage),
+    /// Message sent by the local system to the peer: the local side is the route source.
     Bgp4MpMessageLocal(Bgp4MpMessage),
+    /// Message received from the peer: the peer is the route source.
     Bgp4MpMessageAs4(Bgp4MpMessage),
+    /// Message sent by the local system to the peer: the local side is the route source.
     Bgp4MpMessageAs4Local(Bgp4MpMessage),
 }
 
 /// BGP4MP message subtypes.
-#[derive(Debug, Primitive, Copy, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Primitive, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
 pub enum Bgp4MpType {
     Bgp4MpStateChange = 0,
     Bgp4MpMessage = 1,
@@ -41,8 +66,41 @@ pub enum Bgp4MpType {
     Bgp4MpMessageLocalAs4Addpath = 11,
 }
 
+impl Bgp4MpType {
+    /// Get the numeric subtype code.
+    pub fn code(&self) -> u16 {
+        *self as u16
+    }
+
+    /// Check whether the subtype records messages sent by the local system rather than received
+    /// from the peer, i.e. one of the `*_LOCAL` subtypes.
+    pub fn is_local(&self) -> bool {
+        matches!(self,
+            Bgp4MpType::Bgp4MpMessageLocal | Bgp4MpType::Bgp4MpMessageAs4Local |
+            Bgp4MpType::Bgp4MpMessageLocalAddpath | Bgp4MpType::Bgp4MpMessageLocalAs4Addpath)
+    }
+}
+
+/// Display the canonical subtype name as registered by IANA, e.g. `BGP4MP_MESSAGE_AS4`.
+impl Display for Bgp4MpType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Bgp4MpType::Bgp4MpStateChange => "BGP4MP_STATE_CHANGE",
+            Bgp4MpType::Bgp4MpMessage => "BGP4MP_MESSAGE",
+            Bgp4MpType::Bgp4MpMessageAs4 => "BGP4MP_MESSAGE_AS4",
+            Bgp4MpType::Bgp4MpStateChangeAs4 => "BGP4MP_STATE_CHANGE_AS4",
+            Bgp4MpType::Bgp4MpMessageLocal => "BGP4MP_MESSAGE_LOCAL",
+            Bgp4MpType::Bgp4MpMessageAs4Local => "BGP4MP_MESSAGE_AS4_LOCAL",
+            Bgp4MpType::Bgp4MpMessageAddpath => "BGP4MP_MESSAGE_ADDPATH",
+            Bgp4MpType::Bgp4MpMessageAs4Addpath => "BGP4MP_MESSAGE_AS4_ADDPATH",
+            Bgp4MpType::Bgp4MpMessageLocalAddpath => "BGP4MP_MESSAGE_LOCAL_ADDPATH",
+            Bgp4MpType::Bgp4MpMessageLocalAs4Addpath => "BGP4MP_MESSAGE_LOCAL_AS4_ADDPATH",
+        })
+    }
+}
+
 /// BGP4MP state change message.
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct Bgp4MpStateChange {
     pub msg_type: Bgp4MpType,
     pub peer_asn: Asn,
@@ -55,8 +113,56 @@ pub struct Bgp4MpStateChange {
     pub new_state: BgpState,
 }
 
+impl Bgp4MpStateChange {
+    /// Key identifying the BGP session: `(peer_ip, local_ip, peer_asn, local_asn)`.
+    ///
+    /// The key is the same as [Bgp4MpMessage::session_key] for messages of the same session.
+    pub fn session_key(&self) -> (IpAddr, IpAddr, Asn, Asn) {
+        (self.peer_addr, self.local_addr, self.peer_asn, self.local_asn)
+    }
+
+    /// Convert the state change into a [StateChangeRecord] at the given timestamp.
+    pub fn to_record(&self, timestamp: f64) -> StateChangeRecord {
+        StateChangeRecord {
+            timestamp,
+            peer_ip: self.peer_addr,
+            peer_asn: self.peer_asn,
+            old_state: self.old_state,
+            new_state: self.new_state,
+        }
+    }
+}
+
+/// Display the peer and the transition, e.g. `10.0.0.1 AS65000: Established -> Idle`.
+impl Display for Bgp4MpStateChange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} AS{}: {} -> {}", self.peer_addr, self.peer_asn, self.old_state, self.new_state)
+    }
+}
+
+/// A timestamped BGP session state change, parallel to [BgpElem](crate::bgp::BgpElem) for
+/// announcements and withdrawals.
+///
+/// It displays as `timestamp|peer_ip|peer_asn|old_state|new_state`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StateChangeRecord {
+    pub timestamp: f64,
+    pub peer_ip: IpAddr,
+    pub peer_asn: Asn,
+    pub old_state: BgpState,
+    pub new_state: BgpState,
+}
+
+impl Display for StateChangeRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}|{}|{}|{}|{}",
+               self.timestamp, self.peer_ip, self.peer_asn, self.old_state, self.new_state
+        )
+    }
+}
+
 /// BGP4MP message.
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct Bgp4MpMessage {
     pub msg_type: Bgp4MpType,
     pub peer_asn: Asn,
@@ -68,3 +174,298 @@ pub struct Bgp4MpMessage {
     pub bgp_message: BgpMessage
 }
 
+impl Bgp4MpMessage {
+    /// Key identifying the BGP session: `(peer_ip, local_ip, peer_asn, local_asn)`.
+    pub fn session_key(&self) -> (IpAddr, IpAddr, Asn, Asn) {
+        (self.peer_ip, self.local_ip, self.peer_asn, self.local_asn)
+    }
+
+    /// Address and ASN of the side that sent the recorded message, i.e. the source of its routes.
+    ///
+    /// Messages of the `*_LOCAL` subtypes are sent by the local system, so the source is
+    /// `(local_ip, local_asn)`. All other messages are received from the peer, so the source is
+    /// `(peer_ip, peer_asn)`.
+    pub fn route_source(&self) -> (IpAddr, Asn) {
+        match self.msg_type.is_local() {
+            true => (self.local_ip, self.local_asn),
+            false => (self.peer_ip, self.peer_asn),
+        }
+    }
+
+    /// Convert the message into per-prefix [BgpElem]s at the given timestamp.
+    ///
+    /// Announced prefixes become `ANNOUNCE` elements carrying the decoded attributes, and
+    /// withdrawn prefixes become `WITHDRAW` elements. Prefixes of MP_REACH_NLRI and
+    /// MP_UNREACH_NLRI attributes follow the ones in the message body. Only UPDATE messages carry
+    /// routes: OPEN, NOTIFICATION and KEEPALIVE messages yield no elements.
+    ///
+    /// The next hop of prefixes announced in the message body is the NEXT_HOP attribute, and the
+    /// next hop of MP_REACH_NLRI prefixes is the one of their NLRI ([Nlri::effective_next_hop](crate::bgp::Nlri::effective_next_hop)).
+    ///
+    /// The `peer_ip` and `peer_asn` of the elements are set to the [route source](Self::route_source),
+    /// so elements of `*_LOCAL` messages represent the routes announced by the local system.
+    pub fn to_elems(&self, timestamp: f64) -> Vec<BgpElem> {
+        let update = match &self.bgp_message {
+            BgpMessage::Update(update) => update,
+            BgpMessage::Open(_) | BgpMessage::Notification(_) | BgpMessage::KeepAlive(_) => {
+                return vec![]
+            }
+        };
+
+        let (peer_ip, peer_asn) = self.route_source();
+        let base = BgpElem {
+            timestamp,
+            peer_ip,
+            peer_asn,
+            ..Default::default()
+        };
+        let mut announce = BgpElem {
+            elem_type: ElemType::ANNOUNCE,
+            ..base.clone()
+        };
+        announce.set_attributes(&update.attributes);
+
+        // prefixes in the message body use NEXT_HOP, MP_REACH_NLRI prefixes use their own next hop
+        let body_announce = BgpElem {
+            next_hop: update.attributes.next_hop(),
+            ..announce.clone()
+        };
+        let mut elems: Vec<BgpElem> = update.announced_prefixes.iter()
+            .map(|prefix| BgpElem { prefix: *prefix, ..body_announce.clone() })
+            .collect();
+        let mut withdrawn: Vec<&NetworkPrefix> = update.withdrawn_prefixes.iter().collect();
+        for attr in &update.attributes {
+            match &attr.value {
+                AttributeValue::MpReachNlri(nlri) => {
+                    let mp_announce = BgpElem {
+                        next_hop: nlri.effective_next_hop(),
+                        ..announce.clone()
+                    };
+                    elems.extend(nlri.prefixes.iter().map(|prefix| BgpElem { prefix: *prefix, ..mp_announce.clone() }));
+                }
+                AttributeValue::MpUnreachNlri(nlri) => withdrawn.extend(nlri.prefixes.iter()),
+                _ => {}
+            }
+        }
+
+        elems.extend(withdrawn.into_iter().map(|prefix| BgpElem {
+            elem_type: ElemType::WITHDRAW,
+            prefix: *prefix,
+            ..base.clone()
+        }));
+        elems
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::bgp::*;
+    use crate::network::{NetworkPrefix, NextHopAddress, Safi};
+    use super::*;
+
+    #[test]
+    fn test_bgp_state_ord() {
+        assert!(BgpState::Established > BgpState::Idle);
+        assert!(BgpState::OpenSent < BgpState::OpenConfirm);
+        let states = [BgpState::Active, BgpState::Established, BgpState::Idle];
+        assert_eq!(states.iter().max(), Some(&BgpState::Established));
+    }
+
+    #[test]
+    fn test_bgp4mp_type_display() {
+        assert_eq!(Bgp4MpType::Bgp4MpMessageAs4.to_string(), "BGP4MP_MESSAGE_AS4");
+        assert_eq!(Bgp4MpType::Bgp4MpMessageAs4.code(), 4);
+    }
+
+    fn bgp4mp_message(bgp_message: BgpMessage) -> Bgp4MpMessage {
+        Bgp4MpMessage {
+            msg_type: Bgp4MpType::Bgp4MpMessageAs4,
+            peer_asn: 65000.into(),
+            local_asn: 65001.into(),
+            interface_index: 0,
+            afi: Afi::Ipv4,
+            peer_ip: IpAddr::from_str("10.0.0.1").unwrap(),
+            local_ip: IpAddr::from_str("10.0.0.2").unwrap(),
+            bgp_message,
+        }
+    }
+
+    #[test]
+    fn test_session_key() {
+        let msg1 = bgp4mp_message(BgpMessage::KeepAlive(BgpKeepAliveMessage {}));
+        let msg2 = bgp4mp_message(BgpMessage::Update(BgpUpdateMessage {
+            withdrawn_prefixes: vec![],
+            attributes: vec![],
+            announced_prefixes: vec![],
+        }));
+        assert_eq!(msg1.session_key(), msg2.session_key());
+
+        let other = Bgp4MpMessage { peer_ip: IpAddr::from_str("10.0.0.3").unwrap(), ..msg1.clone() };
+        assert_ne!(msg1.session_key(), other.session_key());
+
+        let state_change = Bgp4MpStateChange {
+            msg_type: Bgp4MpType::Bgp4MpStateChangeAs4,
+            peer_asn: msg1.peer_asn,
+            local_asn: msg1.local_asn,
+            interface_index: 0,
+            address_family: Afi::Ipv4,
+            peer_addr: msg1.peer_ip,
+            local_addr: msg1.local_ip,
+            old_state: BgpState::Established,
+            new_state: BgpState::Idle,
+        };
+        assert_eq!(state_change.session_key(), msg1.session_key());
+    }
+
+    #[test]
+    fn test_to_elems() {
+        let msg = bgp4mp_message(BgpMessage::KeepAlive(BgpKeepAliveMessage {}));
+        assert!(msg.to_elems(1.0).is_empty());
+
+        let msg = bgp4mp_message(BgpMessage::Update(BgpUpdateMessage {
+            withdrawn_prefixes: vec![NetworkPrefix::from_str("10.1.0.0/16").unwrap()],
+            attributes: vec![
+                Attribute {
+                    attr_type: AttrType::AS_PATH,
+                    value: AttributeValue::AsPath(AsPath::from_str("65000 15169").unwrap()),
+                    flag: 0x40,
+                },
+                Attribute {
+                    attr_type: AttrType::NEXT_HOP,
+                    value: AttributeValue::NextHop(IpAddr::from_str("10.0.0.1").unwrap()),
+                    flag: 0x40,
+                },
+            ],
+            announced_prefixes: vec![NetworkPrefix::from_str("8.8.8.0/24").unwrap()],
+        }));
+        let elems = msg.to_elems(1.0);
+        assert_eq!(elems.len(), 2);
+        assert_eq!(elems[0].elem_type, ElemType::ANNOUNCE);
+        assert_eq!(elems[0].prefix, NetworkPrefix::from_str("8.8.8.0/24").unwrap());
+        assert_eq!(elems[0].as_path, Some(AsPath::from_str("65000 15169").unwrap()));
+        assert_eq!(elems[0].origin_asns, Some(OriginAsns::Single(15169.into())));
+        assert_eq!(elems[0].next_hop, Some(IpAddr::from_str("10.0.0.1").unwrap()));
+        assert_eq!(elems[1].elem_type, ElemType::WITHDRAW);
+        assert_eq!(elems[1].prefix, NetworkPrefix::from_str("10.1.0.0/16").unwrap());
+        assert_eq!(elems[1].as_path, None);
+    }
+
+    #[test]
+    fn test_to_elems_multi_prefix() {
+        let prefixes: Vec<NetworkPrefix> = ["8.8.8.0/24", "8.8.4.0/24", "2001:4860::/32"].iter()
+            .map(|p| NetworkPrefix::from_str(p).unwrap())
+            .collect();
+        let msg = bgp4mp_message(BgpMessage::Update(BgpUpdateMessage {
+            withdrawn_prefixes: vec![],
+            attributes: vec![
+                Attribute {
+                    attr_type: AttrType::AS_PATH,
+                    value: AttributeValue::AsPath(AsPath::from_str("65000 15169").unwrap()),
+                    flag: 0x40,
+                },
+                Attribute {
+                    attr_type: AttrType::MP_REACHABLE_NLRI,
+                    value: AttributeValue::MpReachNlri(Nlri {
+                        afi: Afi::Ipv6,
+                        safi: Safi::Unicast,
+                        next_hop: None,
+                        prefixes: prefixes[2..].to_vec(),
+                        flowspec: vec![],
+                    }),
+                    flag: 0x80,
+                },
+                Attribute {
+                    attr_type: AttrType::MP_UNREACHABLE_NLRI,
+                    value: AttributeValue::MpUnreachNlri(Nlri {
+                        afi: Afi::Ipv6,
+                        safi: Safi::Unicast,
+                        next_hop: None,
+                        prefixes: vec![NetworkPrefix::from_str("2001:db8::/32").unwrap()],
+                        flowspec: vec![],
+                    }),
+                    flag: 0x80,
+                },
+            ],
+            announced_prefixes: prefixes[..2].to_vec(),
+        }));
+        let elems = msg.to_elems(1.0);
+        assert_eq!(elems.len(), 4);
+        assert_eq!(elems[..3].iter().map(|e| e.prefix).collect::<Vec<NetworkPrefix>>(), prefixes);
+        assert!(elems[..3].iter().all(|e| e.elem_type == ElemType::ANNOUNCE
+            && e.origin_asns == Some(OriginAsns::Single(15169.into()))));
+        assert_eq!(elems[3].elem_type, ElemType::WITHDRAW);
+        assert_eq!(elems[3].prefix, NetworkPrefix::from_str("2001:db8::/32").unwrap());
+    }
+
+    #[test]
+    fn test_to_elems_mixed_next_hops() {
+        let ipv6_next_hop = std::net::Ipv6Addr::from_str("2001:db8::1").unwrap();
+        let msg = bgp4mp_message(BgpMessage::Update(BgpUpdateMessage {
+            withdrawn_prefixes: vec![],
+            attributes: vec![
+                Attribute {
+                    attr_type: AttrType::NEXT_HOP,
+                    value: AttributeValue::NextHop(IpAddr::from_str("10.0.0.1").unwrap()),
+                    flag: 0x40,
+                },
+                Attribute {
+                    attr_type: AttrType::MP_REACHABLE_NLRI,
+                    value: AttributeValue::MpReachNlri(Nlri {
+                        afi: Afi::Ipv6,
+                        safi: Safi::Unicast,
+                        next_hop: Some(NextHopAddress::Ipv6(ipv6_next_hop)),
+                        prefixes: vec![NetworkPrefix::from_str("2001:4860::/32").unwrap()],
+                        flowspec: vec![],
+                    }),
+                    flag: 0x80,
+                },
+            ],
+            announced_prefixes: vec![NetworkPrefix::from_str("8.8.8.0/24").unwrap()],
+        }));
+        let elems = msg.to_elems(1.0);
+        assert_eq!(elems.len(), 2);
+        assert_eq!(elems[0].prefix, NetworkPrefix::from_str("8.8.8.0/24").unwrap());
+        assert_eq!(elems[0].next_hop, Some(IpAddr::from_str("10.0.0.1").unwrap()));
+        assert_eq!(elems[1].prefix, NetworkPrefix::from_str("2001:4860::/32").unwrap());
+        assert_eq!(elems[1].next_hop, Some(IpAddr::V6(ipv6_next_hop)));
+    }
+
+    #[test]
+    fn test_to_elems_route_source() {
+        let update = BgpMessage::Update(BgpUpdateMessage {
+            withdrawn_prefixes: vec![],
+            attributes: vec![],
+            announced_prefixes: vec![NetworkPrefix::from_str("8.8.8.0/24").unwrap()],
+        });
+        let received = bgp4mp_message(update);
+        assert!(!received.msg_type.is_local());
+        let elems = received.to_elems(1.0);
+        assert_eq!((elems[0].peer_ip, elems[0].peer_asn), (received.peer_ip, received.peer_asn));
+
+        let sent = Bgp4MpMessage { msg_type: Bgp4MpType::Bgp4MpMessageAs4Local, ..received.clone() };
+        assert!(sent.msg_type.is_local());
+        assert_eq!(sent.route_source(), (sent.local_ip, sent.local_asn));
+        let elems = sent.to_elems(1.0);
+        assert_eq!(elems[0].peer_ip, IpAddr::from_str("10.0.0.2").unwrap());
+        assert_eq!(elems[0].peer_asn, 65001u32);
+    }
+
+    #[test]
+    fn test_state_change_record() {
+        let state_change = Bgp4MpStateChange {
+            msg_type: Bgp4MpType::Bgp4MpStateChangeAs4,
+            peer_asn: 65000.into(),
+            local_asn: 65001.into(),
+            interface_index: 0,
+            address_family: Afi::Ipv4,
+            peer_addr: IpAddr::from_str("10.0.0.1").unwrap(),
+            local_addr: IpAddr::from_str("10.0.0.2").unwrap(),
+            old_state: BgpState::Established,
+            new_state: BgpState::Idle,
+        };
+        assert_eq!(state_change.to_string(), "10.0.0.1 AS65000: Established -> Idle");
+        let record = state_change.to_record(1.5);
+        assert_eq!(record.to_string(), "1.5|10.0.0.1|65000|Established|Idle");
+    }
+}
@@ -5,7 +5,14 @@ pub mod bgp4mp;
 
 pub use crate::mrt::bgp4mp::*;
 pub use crate::mrt::tabledump::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use serde::Serialize;
+use crate::bgp::{BgpMessage, BgpUpdateMessage};
+use crate::network::{Afi, Asn};
+use num_traits::FromPrimitive;
 
 /// MrtRecord is a wrapper struct that contains a header and a message.
 ///
@@ -22,12 +29,25 @@ use serde::Serialize;
 ///
 /// See [CommonHeader] for the content in header, and [MrtMessage] for the
 /// message format.
-#[derive(Debug, Serialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, PartialEq, Eq, Clone)]
 pub struct MrtRecord {
     pub common_header: CommonHeader,
     pub message: MrtMessage,
 }
 
+impl MrtRecord {
+    /// Hash the message content, ignoring the common header and thus the timestamp.
+    ///
+    /// Records carrying the same message, e.g. the same UPDATE seen in overlapping dumps, have
+    /// the same content hash. The hash is only stable within one build of the crate, so it should
+    /// not be persisted.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.message.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
 /// MRT common header.
 ///
 /// A CommonHeader ([RFC6396 section 2][header-link]) is constructed as the following:
@@ -77,7 +97,148 @@ pub struct CommonHeader {
     pub length: u32,
 }
 
-#[derive(Debug, Serialize, PartialEq, Eq, Clone)]
+impl CommonHeader {
+    /// Get the timestamp in seconds, including the microsecond part of extended timestamp
+    /// records. Records without the microsecond part have whole-second timestamps.
+    pub fn timestamp_secs(&self) -> f64 {
+        self.timestamp as f64 + self.microsecond_timestamp.unwrap_or(0) as f64 / 1_000_000.0
+    }
+
+    /// Get the timestamp as a [SystemTime].
+    pub fn as_system_time(&self) -> SystemTime {
+        UNIX_EPOCH
+            + Duration::from_secs(self.timestamp as u64)
+            + Duration::from_micros(self.microsecond_timestamp.unwrap_or(0) as u64)
+    }
+
+    /// Decode `entry_subtype` against `entry_type`.
+    ///
+    /// BGP4MP and BGP4MP_ET subtypes decode into [Bgp4MpType], TABLE_DUMP_V2 subtypes into
+    /// [TableDumpV2Type], and TABLE_DUMP subtypes into the [Afi] of the entry. Subtypes of other
+    /// entry types, and unknown subtypes, are returned as [MrtSubtype::Other].
+    pub fn typed_subtype(&self) -> MrtSubtype {
+        let subtype = self.entry_subtype;
+        let typed = match self.entry_type {
+            EntryType::BGP4MP | EntryType::BGP4MP_ET => {
+                Bgp4MpType::from_u16(subtype).map(MrtSubtype::Bgp4Mp)
+            }
+            EntryType::TABLE_DUMP_V2 => {
+                TableDumpV2Type::from_u16(subtype).map(MrtSubtype::TableDumpV2)
+            }
+            EntryType::TABLE_DUMP => {
+                Afi::from_u16(subtype).map(MrtSubtype::TableDump)
+            }
+            _ => None,
+        };
+        typed.unwrap_or(MrtSubtype::Other(subtype))
+    }
+}
+
+/// MRT subtype decoded against the entry type, see [CommonHeader::typed_subtype].
+#[derive(Debug, Copy, Clone, Serialize, PartialEq, Eq)]
+pub enum MrtSubtype {
+    Bgp4Mp(Bgp4MpType),
+    TableDumpV2(TableDumpV2Type),
+    TableDump(Afi),
+    Other(u16),
+}
+
+/// Builder of [MrtRecord]s, mainly for constructing test fixtures.
+///
+/// The common header is filled with the entry type and subtype matching the message. If a
+/// microsecond timestamp is set, BGP4MP records use the `BGP4MP_ET` type. Since messages are not
+/// encoded here, the header `length` is `0` unless set with [MrtRecordBuilder::length].
+///
+/// ```
+/// use bgp_models::prelude::*;
+///
+/// let table = PeerIndexTable {
+///     collector_bgp_id: "10.0.0.1".parse().unwrap(),
+///     view_name_length: 0,
+///     view_name: "".to_string(),
+///     peer_count: 0,
+///     peers_map: Default::default(),
+/// };
+/// let record = MrtRecordBuilder::new(1609459200).peer_index_table(table);
+/// assert_eq!(record.common_header.entry_type, EntryType::TABLE_DUMP_V2);
+/// assert_eq!(record.common_header.entry_subtype, 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MrtRecordBuilder {
+    timestamp: u32,
+    microsecond_timestamp: Option<u32>,
+    length: u32,
+}
+
+impl MrtRecordBuilder {
+    pub fn new(timestamp: u32) -> MrtRecordBuilder {
+        MrtRecordBuilder { timestamp, ..Default::default() }
+    }
+
+    pub fn microsecond_timestamp(mut self, microsecond_timestamp: u32) -> Self {
+        self.microsecond_timestamp = Some(microsecond_timestamp);
+        self
+    }
+
+    pub fn length(mut self, length: u32) -> Self {
+        self.length = length;
+        self
+    }
+
+    fn header(&self, entry_type: EntryType, entry_subtype: u16) -> CommonHeader {
+        CommonHeader {
+            timestamp: self.timestamp,
+            microsecond_timestamp: self.microsecond_timestamp,
+            entry_type,
+            entry_subtype,
+            length: self.length,
+        }
+    }
+
+    /// Build a `BGP4MP_MESSAGE_AS4` record carrying a BGP UPDATE message.
+    pub fn bgp4mp_update(self, peer_ip: IpAddr, peer_asn: Asn, local_ip: IpAddr, local_asn: Asn,
+                         update: BgpUpdateMessage) -> MrtRecord {
+        let entry_type = match self.microsecond_timestamp {
+            Some(_) => EntryType::BGP4MP_ET,
+            None => EntryType::BGP4MP,
+        };
+        let msg = Bgp4MpMessage {
+            msg_type: Bgp4MpType::Bgp4MpMessageAs4,
+            peer_asn,
+            local_asn,
+            interface_index: 0,
+            afi: match peer_ip {
+                IpAddr::V4(_) => Afi::Ipv4,
+                IpAddr::V6(_) => Afi::Ipv6,
+            },
+            peer_ip,
+            local_ip,
+            bgp_message: BgpMessage::Update(update),
+        };
+        MrtRecord {
+            common_header: self.header(entry_type, Bgp4MpType::Bgp4MpMessageAs4.code()),
+            message: MrtMessage::Bgp4Mp(Bgp4Mp::Bgp4MpMessageAs4(msg)),
+        }
+    }
+
+    /// Build a `TABLE_DUMP_V2` RIB record, with the subtype taken from `rib.rib_type`.
+    pub fn tabledump_v2_rib(self, rib: RibAfiEntries) -> MrtRecord {
+        MrtRecord {
+            common_header: self.header(EntryType::TABLE_DUMP_V2, rib.rib_type.code()),
+            message: MrtMessage::TableDumpV2Message(TableDumpV2Message::RibAfiEntries(rib)),
+        }
+    }
+
+    /// Build a `TABLE_DUMP_V2` `PEER_INDEX_TABLE` record.
+    pub fn peer_index_table(self, table: PeerIndexTable) -> MrtRecord {
+        MrtRecord {
+            common_header: self.header(EntryType::TABLE_DUMP_V2, TableDumpV2Type::PeerIndexTable.code()),
+            message: MrtMessage::TableDumpV2Message(TableDumpV2Message::PeerIndexTable(table)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq, Clone, Hash)]
 pub enum MrtMessage {
     TableDumpMessage(TableDumpMessage),
     TableDumpV2Message(TableDumpV2Message),
@@ -133,3 +294,140 @@ pub enum EntryType {
     OSPFv3_ET = 49,
 }
 
+impl EntryType {
+    /// Check whether the entry type is one of the deprecated types (codes 0 to 10) listed in
+    /// [RFC 6396 appendix B](https://datatracker.ietf.org/doc/html/rfc6396#appendix-B).
+    pub fn is_deprecated(&self) -> bool {
+        (*self as u16) <= EntryType::BGP4PLUS_01 as u16
+    }
+
+    /// Check whether the entry type can be represented by [MrtMessage], i.e. it is one of
+    /// TABLE_DUMP, TABLE_DUMP_V2, BGP4MP or BGP4MP_ET.
+    pub fn is_supported(&self) -> bool {
+        matches!(self, EntryType::TABLE_DUMP | EntryType::TABLE_DUMP_V2 | EntryType::BGP4MP | EntryType::BGP4MP_ET)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::network::NetworkPrefix;
+    use super::*;
+
+    #[test]
+    fn test_entry_type_classification() {
+        let types: Vec<EntryType> = (0..=49).filter_map(EntryType::from_u16).collect();
+        assert_eq!(types.len(), 20);
+        for entry_type in types {
+            let code = entry_type as u16;
+            assert_eq!(entry_type.is_deprecated(), code <= 10, "{:?}", entry_type);
+            assert_eq!(entry_type.is_supported(), matches!(code, 12 | 13 | 16 | 17), "{:?}", entry_type);
+            assert!(!(entry_type.is_deprecated() && entry_type.is_supported()));
+        }
+    }
+
+    #[test]
+    fn test_header_timestamps() {
+        let header = CommonHeader {
+            timestamp: 1609459200,
+            microsecond_timestamp: None,
+            entry_type: EntryType::BGP4MP,
+            entry_subtype: 4,
+            length: 0,
+        };
+        assert_eq!(header.timestamp_secs(), 1609459200.0);
+        assert_eq!(header.as_system_time(), UNIX_EPOCH + Duration::from_secs(1609459200));
+
+        let header = CommonHeader {
+            microsecond_timestamp: Some(250000),
+            entry_type: EntryType::BGP4MP_ET,
+            ..header
+        };
+        assert_eq!(header.timestamp_secs(), 1609459200.25);
+        assert_eq!(header.as_system_time(), UNIX_EPOCH + Duration::from_millis(1609459200250));
+    }
+
+    #[test]
+    fn test_typed_subtype() {
+        let header = |entry_type: EntryType, entry_subtype: u16| CommonHeader {
+            timestamp: 0,
+            microsecond_timestamp: None,
+            entry_type,
+            entry_subtype,
+            length: 0,
+        };
+        assert_eq!(header(EntryType::BGP4MP, 4).typed_subtype(), MrtSubtype::Bgp4Mp(Bgp4MpType::Bgp4MpMessageAs4));
+        assert_eq!(header(EntryType::BGP4MP_ET, 5).typed_subtype(), MrtSubtype::Bgp4Mp(Bgp4MpType::Bgp4MpStateChangeAs4));
+        assert_eq!(header(EntryType::BGP4MP, 2).typed_subtype(), MrtSubtype::Other(2));
+        assert_eq!(header(EntryType::TABLE_DUMP_V2, 2).typed_subtype(), MrtSubtype::TableDumpV2(TableDumpV2Type::RibIpv4Unicast));
+        assert_eq!(header(EntryType::TABLE_DUMP_V2, 13).typed_subtype(), MrtSubtype::Other(13));
+        assert_eq!(header(EntryType::TABLE_DUMP, 2).typed_subtype(), MrtSubtype::TableDump(Afi::Ipv6));
+        assert_eq!(header(EntryType::BGP, 1).typed_subtype(), MrtSubtype::Other(1));
+    }
+
+    #[test]
+    fn test_record_builder() {
+        let update = BgpUpdateMessage {
+            withdrawn_prefixes: vec![],
+            attributes: vec![],
+            announced_prefixes: vec![NetworkPrefix::from_str("8.8.8.0/24").unwrap()],
+        };
+        let record = MrtRecordBuilder::new(1609459200)
+            .length(64)
+            .bgp4mp_update(
+                IpAddr::from_str("10.0.0.1").unwrap(), 65000.into(),
+                IpAddr::from_str("10.0.0.2").unwrap(), 65001.into(),
+                update.clone(),
+            );
+        assert_eq!(record.common_header, CommonHeader {
+            timestamp: 1609459200,
+            microsecond_timestamp: None,
+            entry_type: EntryType::BGP4MP,
+            entry_subtype: 4,
+            length: 64,
+        });
+        match &record.message {
+            MrtMessage::Bgp4Mp(Bgp4Mp::Bgp4MpMessageAs4(msg)) => {
+                assert_eq!(msg.afi, Afi::Ipv4);
+                assert_eq!(msg.bgp_message, BgpMessage::Update(update.clone()));
+            }
+            m => panic!("unexpected message {:?}", m),
+        }
+
+        let record = MrtRecordBuilder::new(1609459200)
+            .microsecond_timestamp(500)
+            .bgp4mp_update(
+                IpAddr::from_str("2001:db8::1").unwrap(), 65000.into(),
+                IpAddr::from_str("2001:db8::2").unwrap(), 65001.into(),
+                update,
+            );
+        assert_eq!(record.common_header.entry_type, EntryType::BGP4MP_ET);
+        assert_eq!(record.common_header.microsecond_timestamp, Some(500));
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let update = BgpUpdateMessage {
+            withdrawn_prefixes: vec![],
+            attributes: vec![],
+            announced_prefixes: vec![NetworkPrefix::from_str("8.8.8.0/24").unwrap()],
+        };
+        let record = |timestamp: u32, update: BgpUpdateMessage| MrtRecordBuilder::new(timestamp)
+            .bgp4mp_update(
+                IpAddr::from_str("10.0.0.1").unwrap(), 65000.into(),
+                IpAddr::from_str("10.0.0.2").unwrap(), 65001.into(),
+                update,
+            );
+
+        let record1 = record(1609459200, update.clone());
+        let record2 = record(1609459260, update.clone());
+        assert_ne!(record1, record2);
+        assert_eq!(record1.content_hash(), record2.content_hash());
+
+        let other = BgpUpdateMessage {
+            announced_prefixes: vec![NetworkPrefix::from_str("1.1.1.0/24").unwrap()],
+            ..update
+        };
+        assert_ne!(record1.content_hash(), record(1609459200, other).content_hash());
+    }
+}
@@ -1,12 +1,14 @@
 //! MRT table dump version 1 and 2 structs
+use std::fmt::{Display, Formatter};
 use std::net::{IpAddr, Ipv4Addr};
 use std::collections::HashMap;
-use crate::network::{Afi, Asn, NetworkPrefix, Safi};
+use std::hash::{Hash, Hasher};
+use crate::network::{Afi, Asn, AsnLength, NetworkPrefix, Safi};
 use serde::Serialize;
-use crate::bgp::Attribute;
+use crate::bgp::{AsPath, Attribute, AttributesExt, BgpElem, Community, ElemType, Origin};
 
 /// TableDump message version 1
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct TableDumpMessage {
     pub view_number: u16,
     pub sequence_number: u16,
@@ -18,18 +20,28 @@ pub struct TableDumpMessage {
     pub attributes: Vec<Attribute>,
 }
 
+/// Display a one-line summary, e.g. `seq=1 prefix=8.8.8.0/24 peer=AS65000@10.0.0.1 attrs=3`.
+impl Display for TableDumpMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "seq={} prefix={} peer=AS{}@{} attrs={}",
+               self.sequence_number, self.prefix, self.peer_asn, self.peer_address, self.attributes.len()
+        )
+    }
+}
+
 /// TableDump message version 2 enum
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub enum TableDumpV2Message {
     PeerIndexTable(PeerIndexTable),
     RibAfiEntries(RibAfiEntries),
     RibGenericEntries(RibGenericEntries),
+    GeoPeerTable(GeoPeerTable),
 }
 
 /// TableDump version 2 subtypes.
 ///
 /// <https://www.iana.org/assignments/mrt/mrt.xhtml#subtype-codes>
-#[derive(Debug, Primitive, Copy, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Primitive, Copy, Clone, Serialize, PartialEq, Eq, Hash)]
 pub enum TableDumpV2Type{
     PeerIndexTable = 1,
     RibIpv4Unicast = 2,
@@ -45,6 +57,32 @@ pub enum TableDumpV2Type{
     RibGenericAddPath = 12,
 }
 
+impl TableDumpV2Type {
+    /// Get the numeric subtype code.
+    pub fn code(&self) -> u16 {
+        *self as u16
+    }
+}
+
+/// Display the canonical subtype name as registered by IANA, e.g. `RIB_IPV6_UNICAST`.
+impl Display for TableDumpV2Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            TableDumpV2Type::PeerIndexTable => "PEER_INDEX_TABLE",
+            TableDumpV2Type::RibIpv4Unicast => "RIB_IPV4_UNICAST",
+            TableDumpV2Type::RibIpv4Multicast => "RIB_IPV4_MULTICAST",
+            TableDumpV2Type::RibIpv6Unicast => "RIB_IPV6_UNICAST",
+            TableDumpV2Type::RibIpv6Multicast => "RIB_IPV6_MULTICAST",
+            TableDumpV2Type::RibGeneric => "RIB_GENERIC",
+            TableDumpV2Type::GeoPeerTable => "GEO_PEER_TABLE",
+            TableDumpV2Type::RibIpv4UnicastAddPath => "RIB_IPV4_UNICAST_ADDPATH",
+            TableDumpV2Type::RibIpv4MulticastAddPath => "RIB_IPV4_MULTICAST_ADDPATH",
+            TableDumpV2Type::RibIpv6UnicastAddPath => "RIB_IPV6_UNICAST_ADDPATH",
+            TableDumpV2Type::RibIpv6MulticastAddPath => "RIB_IPV6_MULTICAST_ADDPATH",
+            TableDumpV2Type::RibGenericAddPath => "RIB_GENERIC_ADDPATH",
+        })
+    }
+}
 
 /// AFI/SAFI-Specific RIB Subtypes.
 ///
@@ -74,7 +112,7 @@ pub enum TableDumpV2Type{
 ///        |         Entry Count           |  RIB Entries (variable)
 ///        +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
 /// ```
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct RibAfiEntries{
     pub rib_type: TableDumpV2Type,
     pub sequence_number: u32,
@@ -82,6 +120,31 @@ pub struct RibAfiEntries{
     pub rib_entries: Vec<RibEntry>,
 }
 
+impl RibAfiEntries {
+    /// Convert the RIB entries into `ANNOUNCE` [BgpElem]s at the given timestamp, one per entry.
+    ///
+    /// The peer of each entry is looked up by its `peer_index` in `peers`; entries whose index is
+    /// not in the table are skipped. The `path_id` of ADD-PATH entries is set on the element's
+    /// prefix.
+    pub fn to_elems(&self, peers: &PeerIndexTable, timestamp: f64) -> Vec<BgpElem> {
+        self.rib_entries.iter()
+            .filter_map(|entry| {
+                let peer = peers.peers_map.get(&(entry.peer_index as u32))?;
+                let mut elem = BgpElem {
+                    timestamp,
+                    elem_type: ElemType::ANNOUNCE,
+                    peer_ip: peer.peer_address,
+                    peer_asn: peer.peer_asn,
+                    prefix: NetworkPrefix::new(self.prefix.prefix, entry.path_id.unwrap_or(self.prefix.path_id)),
+                    ..Default::default()
+                };
+                elem.set_attributes(&entry.attributes);
+                Some(elem)
+            })
+            .collect()
+    }
+}
+
 /// RIB generic entries subtype.
 ///
 /// ```text
@@ -103,7 +166,7 @@ pub struct RibAfiEntries{
 ///        |         Entry Count           |  RIB Entries (variable)
 ///        +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
 /// ```
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct RibGenericEntries{
     pub sequence_number: u32,
     pub afi: Afi,
@@ -133,13 +196,54 @@ pub struct RibGenericEntries{
 ///        |                    BGP Attributes... (variable)
 ///        +-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+-+
 /// ```
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+///
+/// The `*_ADDPATH` subtypes ([RFC8050](https://datatracker.ietf.org/doc/html/rfc8050#section-4))
+/// carry a 4-byte path identifier after the originated time. `path_id` is only populated for
+/// entries of those subtypes and is `None` otherwise. It is distinct from the path ID of the
+/// entries' [NetworkPrefix].
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct RibEntry {
     pub peer_index: u16,
     pub originated_time: u32,
+    pub path_id: Option<u32>,
     pub attributes: Vec<Attribute>
 }
 
+/// Attribute accessors, see [AttributesExt].
+impl RibEntry {
+    /// Get the AS_PATH attribute value, if present.
+    pub fn as_path(&self) -> Option<&AsPath> {
+        self.attributes.as_slice().as_path()
+    }
+
+    /// Get the ORIGIN attribute value, if present.
+    pub fn origin(&self) -> Option<Origin> {
+        self.attributes.as_slice().origin()
+    }
+
+    /// Get the NEXT_HOP attribute value, if present.
+    pub fn next_hop(&self) -> Option<IpAddr> {
+        self.attributes.as_slice().next_hop()
+    }
+
+    /// Get the COMMUNITIES attribute value, if present.
+    pub fn communities(&self) -> Option<&[Community]> {
+        self.attributes.as_slice().communities()
+    }
+}
+
+/// Display a one-line summary, e.g. `peer_idx=0 time=1609459200 attrs=4`. The path ID of ADD-PATH
+/// entries follows the originated time, e.g. `peer_idx=0 time=1609459200 path_id=1 attrs=4`.
+impl Display for RibEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "peer_idx={} time={}", self.peer_index, self.originated_time)?;
+        if let Some(path_id) = self.path_id {
+            write!(f, " path_id={}", path_id)?;
+        }
+        write!(f, " attrs={}", self.attributes.len())
+    }
+}
+
 /// peer index table.
 ///
 /// ```text
@@ -160,11 +264,353 @@ pub struct PeerIndexTable{
     pub peers_map: HashMap<u32, Peer>
 }
 
+/// Hash the peers in the order of their index, so that equal tables hash equally.
+impl Hash for PeerIndexTable {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.collector_bgp_id.hash(state);
+        self.view_name_length.hash(state);
+        self.view_name.hash(state);
+        self.peer_count.hash(state);
+        let mut peers: Vec<(&u32, &Peer)> = self.peers_map.iter().collect();
+        peers.sort_by_key(|(index, _)| **index);
+        peers.hash(state);
+    }
+}
+
 /// Peer struct.
-#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+///
+/// `peer_type` holds the flags of the peer entry
+/// ([RFC6396 section 4.3.1](https://datatracker.ietf.org/doc/html/rfc6396#section-4.3.1)):
+/// ```text
+///  0 1 2 3 4 5 6 7
+/// +-+-+-+-+-+-+-+-+
+/// | | | | | | |A|I|
+/// +-+-+-+-+-+-+-+-+
+///
+/// Bit 6: Peer AS number size:  0 = 16 bits, 1 = 32 bits
+/// Bit 7: Peer IP Address family:  0 = IPv4,  1 = IPv6
+/// ```
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, Hash)]
 pub struct Peer {
     pub peer_type: u8,
     pub peer_bgp_id: Ipv4Addr,
     pub peer_address: IpAddr,
     pub peer_asn: Asn,
-}
\ No newline at end of file
+}
+
+impl Peer {
+    /// Check whether the peer type flags mark the peer address as IPv6.
+    pub fn is_ipv6(&self) -> bool {
+        self.peer_type & 0x01 != 0
+    }
+
+    /// Check whether the peer type flags mark the peer ASN as 32-bit.
+    pub fn is_asn_32bit(&self) -> bool {
+        self.peer_type & 0x02 != 0
+    }
+
+    /// Get the peer ASN length according to the peer type flags.
+    pub fn peer_asn_length(&self) -> AsnLength {
+        match self.is_asn_32bit() {
+            true => AsnLength::Bits32,
+            false => AsnLength::Bits16,
+        }
+    }
+}
+
+/// Geo-location peer table.
+///
+/// ```text
+///    The GEO_PEER_TABLE is an optional ancillary record that may be
+///    included in a TABLE_DUMP_V2 MRT file.  It provides the BGP ID of the
+///    collector, its latitude and longitude, and a list of peers with
+///    their latitude and longitude.
+/// ```
+///
+/// See [RFC6397](https://datatracker.ietf.org/doc/html/rfc6397). Coordinates are in degrees;
+/// an unknown location is encoded as NaN, so coordinates are compared by their bit patterns.
+#[derive(Debug, Clone, Serialize)]
+pub struct GeoPeerTable {
+    pub collector_bgp_id: Ipv4Addr,
+    pub collector_latitude: f32,
+    pub collector_longitude: f32,
+    pub geo_peers: Vec<GeoPeer>,
+}
+
+/// Peer entry of a [GeoPeerTable].
+#[derive(Debug, Clone, Serialize)]
+pub struct GeoPeer {
+    pub peer: Peer,
+    pub peer_latitude: f32,
+    pub peer_longitude: f32,
+}
+
+impl PartialEq for GeoPeerTable {
+    fn eq(&self, other: &Self) -> bool {
+        self.collector_bgp_id == other.collector_bgp_id
+            && self.collector_latitude.to_bits() == other.collector_latitude.to_bits()
+            && self.collector_longitude.to_bits() == other.collector_longitude.to_bits()
+            && self.geo_peers == other.geo_peers
+    }
+}
+
+impl Eq for GeoPeerTable {}
+
+impl Hash for GeoPeerTable {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.collector_bgp_id.hash(state);
+        self.collector_latitude.to_bits().hash(state);
+        self.collector_longitude.to_bits().hash(state);
+        self.geo_peers.hash(state);
+    }
+}
+
+impl PartialEq for GeoPeer {
+    fn eq(&self, other: &Self) -> bool {
+        self.peer == other.peer
+            && self.peer_latitude.to_bits() == other.peer_latitude.to_bits()
+            && self.peer_longitude.to_bits() == other.peer_longitude.to_bits()
+    }
+}
+
+impl Eq for GeoPeer {}
+
+impl Hash for GeoPeer {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.peer.hash(state);
+        self.peer_latitude.to_bits().hash(state);
+        self.peer_longitude.to_bits().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+    use crate::bgp::{AsPathSegment, AttrType, AttributeValue};
+    use crate::network::bgp_id_to_ipv4;
+    use super::*;
+
+    #[test]
+    fn test_table_dump_v2_type_display() {
+        assert_eq!(TableDumpV2Type::RibIpv6Unicast.to_string(), "RIB_IPV6_UNICAST");
+        assert_eq!(TableDumpV2Type::RibIpv6Unicast.code(), 4);
+    }
+
+    #[test]
+    fn test_peer_type_flags() {
+        let peer = |peer_type: u8| Peer {
+            peer_type,
+            peer_bgp_id: Ipv4Addr::from_str("10.0.0.1").unwrap(),
+            peer_address: IpAddr::from_str("10.0.0.1").unwrap(),
+            peer_asn: 65000.into(),
+        };
+        let cases = [
+            (0, false, false, AsnLength::Bits16),
+            (1, true, false, AsnLength::Bits16),
+            (2, false, true, AsnLength::Bits32),
+            (3, true, true, AsnLength::Bits32),
+        ];
+        for (peer_type, is_ipv6, is_asn_32bit, asn_len) in cases {
+            let p = peer(peer_type);
+            assert_eq!(p.is_ipv6(), is_ipv6);
+            assert_eq!(p.is_asn_32bit(), is_asn_32bit);
+            assert_eq!(p.peer_asn_length(), asn_len);
+        }
+    }
+
+    #[test]
+    fn test_collector_bgp_id_display() {
+        let table = PeerIndexTable {
+            collector_bgp_id: bgp_id_to_ipv4(0xc0a80101),
+            view_name_length: 0,
+            view_name: "".to_string(),
+            peer_count: 0,
+            peers_map: HashMap::new(),
+        };
+        assert_eq!(table.collector_bgp_id.to_string(), "192.168.1.1");
+        assert_eq!(serde_json::json!(table)["collector_bgp_id"], "192.168.1.1");
+    }
+
+    #[test]
+    fn test_rib_entry_accessors() {
+        let as_path = AsPath::from_segments(vec![
+            AsPathSegment::AsSequence([1,2,3].map(|i|{i.into()}).to_vec()),
+        ]);
+        let entry = RibEntry {
+            peer_index: 0,
+            originated_time: 0,
+            path_id: None,
+            attributes: vec![
+                Attribute { attr_type: AttrType::ORIGIN, value: AttributeValue::Origin(Origin::IGP), flag: 0x40 },
+                Attribute { attr_type: AttrType::AS_PATH, value: AttributeValue::AsPath(as_path.clone()), flag: 0x40 },
+                Attribute { attr_type: AttrType::NEXT_HOP, value: AttributeValue::NextHop(IpAddr::from_str("10.0.0.1").unwrap()), flag: 0x40 },
+                Attribute { attr_type: AttrType::COMMUNITIES, value: AttributeValue::Communities(vec![Community::NoExport]), flag: 0xc0 },
+            ]
+        };
+        assert_eq!(entry.as_path(), Some(&as_path));
+        assert_eq!(entry.origin(), Some(Origin::IGP));
+        assert_eq!(entry.next_hop(), Some(IpAddr::from_str("10.0.0.1").unwrap()));
+        assert_eq!(entry.communities(), Some([Community::NoExport].as_ref()));
+
+        let empty = RibEntry { peer_index: 0, originated_time: 0, path_id: None, attributes: vec![] };
+        assert_eq!(empty.as_path(), None);
+        assert_eq!(empty.origin(), None);
+        assert_eq!(empty.next_hop(), None);
+        assert_eq!(empty.communities(), None);
+    }
+
+    #[test]
+    fn test_geo_peer_table() {
+        let peer = Peer {
+            peer_type: 0,
+            peer_bgp_id: Ipv4Addr::from_str("10.0.0.1").unwrap(),
+            peer_address: IpAddr::from_str("10.0.0.1").unwrap(),
+            peer_asn: 65000.into(),
+        };
+        let table = GeoPeerTable {
+            collector_bgp_id: Ipv4Addr::from_str("192.168.1.1").unwrap(),
+            collector_latitude: 51.5,
+            collector_longitude: -0.125,
+            geo_peers: vec![
+                GeoPeer { peer: peer.clone(), peer_latitude: 40.75, peer_longitude: -73.5 },
+                GeoPeer { peer, peer_latitude: f32::NAN, peer_longitude: f32::NAN },
+            ],
+        };
+        assert_eq!(table.geo_peers.len(), 2);
+        assert_eq!(table, table.clone());
+
+        let message = TableDumpV2Message::GeoPeerTable(table);
+        let debug = format!("{:?}", message);
+        assert!(debug.starts_with("GeoPeerTable(GeoPeerTable { collector_bgp_id: 192.168.1.1, collector_latitude: 51.5, collector_longitude: -0.125"));
+        assert!(debug.contains("peer_latitude: 40.75, peer_longitude: -73.5"));
+        assert!(debug.contains("peer_latitude: NaN, peer_longitude: NaN"));
+    }
+
+    #[test]
+    fn test_rib_add_path_entry() {
+        let prefix = NetworkPrefix::from_str("10.0.0.0/24").unwrap();
+        let rib = RibAfiEntries {
+            rib_type: TableDumpV2Type::RibIpv4UnicastAddPath,
+            sequence_number: 1,
+            prefix,
+            rib_entries: vec![
+                RibEntry { peer_index: 0, originated_time: 0, path_id: Some(1), attributes: vec![] },
+                RibEntry { peer_index: 0, originated_time: 0, path_id: Some(2), attributes: vec![] },
+            ],
+        };
+        let path_ids: Vec<Option<u32>> = rib.rib_entries.iter().map(|e| e.path_id).collect();
+        assert_eq!(path_ids, vec![Some(1), Some(2)]);
+        assert_eq!(serde_json::json!(rib.rib_entries[0])["path_id"], 1);
+    }
+
+    #[test]
+    fn test_rib_to_elems() {
+        let peer = |index: u8, asn: u32| Peer {
+            peer_type: 2,
+            peer_bgp_id: Ipv4Addr::new(10, 0, 0, index),
+            peer_address: IpAddr::from(Ipv4Addr::new(10, 0, 0, index)),
+            peer_asn: asn.into(),
+        };
+        let table = PeerIndexTable {
+            collector_bgp_id: Ipv4Addr::from_str("192.168.1.1").unwrap(),
+            view_name_length: 0,
+            view_name: "".to_string(),
+            peer_count: 2,
+            peers_map: vec![(0, peer(1, 65001)), (1, peer(2, 65002))].into_iter().collect(),
+        };
+        let as_path = |s: &str| Attribute {
+            attr_type: AttrType::AS_PATH,
+            value: AttributeValue::AsPath(AsPath::from_str(s).unwrap()),
+            flag: 0x40,
+        };
+        let rib = RibAfiEntries {
+            rib_type: TableDumpV2Type::RibIpv4Unicast,
+            sequence_number: 1,
+            prefix: NetworkPrefix::from_str("8.8.8.0/24").unwrap(),
+            rib_entries: vec![
+                RibEntry { peer_index: 0, originated_time: 0, path_id: None, attributes: vec![as_path("65001 15169")] },
+                RibEntry { peer_index: 1, originated_time: 0, path_id: None, attributes: vec![as_path("65002 3356 15169")] },
+                RibEntry { peer_index: 5, originated_time: 0, path_id: None, attributes: vec![] },
+            ],
+        };
+
+        let elems = rib.to_elems(&table, 1.5);
+        assert_eq!(elems.len(), 2);
+        assert_eq!(elems[0].peer_ip, IpAddr::from_str("10.0.0.1").unwrap());
+        assert_eq!(elems[0].peer_asn, 65001u32);
+        assert_eq!(elems[1].peer_ip, IpAddr::from_str("10.0.0.2").unwrap());
+        assert_eq!(elems[1].peer_asn, 65002u32);
+        assert_eq!(elems[1].as_path, Some(AsPath::from_str("65002 3356 15169").unwrap()));
+        assert!(elems.iter().all(|e| e.prefix == rib.prefix && e.timestamp == 1.5
+            && e.elem_type == ElemType::ANNOUNCE));
+    }
+
+    #[test]
+    fn test_table_dump_v2_clone() {
+        let mut original = TableDumpV2Message::RibAfiEntries(RibAfiEntries {
+            rib_type: TableDumpV2Type::RibIpv4Unicast,
+            sequence_number: 1,
+            prefix: NetworkPrefix::from_str("8.8.8.0/24").unwrap(),
+            rib_entries: vec![
+                RibEntry { peer_index: 0, originated_time: 0, path_id: None, attributes: vec![] },
+            ],
+        });
+        let cloned = original.clone();
+        if let TableDumpV2Message::RibAfiEntries(rib) = &mut original {
+            rib.rib_entries[0].attributes.push(
+                Attribute { attr_type: AttrType::ORIGIN, value: AttributeValue::Origin(Origin::IGP), flag: 0x40 }
+            );
+            rib.rib_entries.clear();
+        }
+        assert_ne!(original, cloned);
+        match cloned {
+            TableDumpV2Message::RibAfiEntries(rib) => {
+                assert_eq!(rib.rib_entries.len(), 1);
+                assert!(rib.rib_entries[0].attributes.is_empty());
+            }
+            v => panic!("unexpected message {:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_table_dump_serialize() {
+        let msg = TableDumpMessage {
+            view_number: 0,
+            sequence_number: 1,
+            prefix: NetworkPrefix::from_str("8.8.8.0/24").unwrap(),
+            status: 1,
+            originated_time: 1609459200,
+            peer_address: IpAddr::from_str("10.0.0.1").unwrap(),
+            peer_asn: 65000.into(),
+            attributes: vec![
+                Attribute { attr_type: AttrType::ORIGIN, value: AttributeValue::Origin(Origin::IGP), flag: 0x40 },
+            ],
+        };
+        let value = serde_json::json!(msg);
+        assert_eq!(value["sequence_number"], 1);
+        assert_eq!(value["peer_address"], "10.0.0.1");
+        assert_eq!(value["peer_asn"], 65000);
+        assert_eq!(value["attributes"][0]["value"]["Origin"], "IGP");
+    }
+
+    #[test]
+    fn test_table_dump_display() {
+        let origin = Attribute { attr_type: AttrType::ORIGIN, value: AttributeValue::Origin(Origin::IGP), flag: 0x40 };
+        let msg = TableDumpMessage {
+            view_number: 0,
+            sequence_number: 1,
+            prefix: NetworkPrefix::from_str("8.8.8.0/24").unwrap(),
+            status: 1,
+            originated_time: 1609459200,
+            peer_address: IpAddr::from_str("10.0.0.1").unwrap(),
+            peer_asn: 65000.into(),
+            attributes: vec![origin.clone()],
+        };
+        assert_eq!(msg.to_string(), "seq=1 prefix=8.8.8.0/24 peer=AS65000@10.0.0.1 attrs=1");
+
+        let entry = RibEntry { peer_index: 3, originated_time: 1609459200, path_id: None, attributes: vec![origin] };
+        assert_eq!(entry.to_string(), "peer_idx=3 time=1609459200 attrs=1");
+        let entry = RibEntry { path_id: Some(7), attributes: vec![], ..entry };
+        assert_eq!(entry.to_string(), "peer_idx=3 time=1609459200 path_id=7 attrs=0");
+    }
+}
@@ -1,10 +1,14 @@
 //! Common network-related structs.
 
+use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
-use std::net::{Ipv4Addr, Ipv6Addr};
+use std::collections::{BTreeMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
 use ipnetwork::IpNetwork;
-use serde::{Serialize, Serializer, Deserialize};
+use num_traits::FromPrimitive;
+use serde::{de, Serialize, Serializer, Deserialize, Deserializer};
 use crate::err::BgpModelsError;
 
 /// Meta information for an address/prefix.
@@ -15,12 +19,34 @@ use crate::err::BgpModelsError;
 /// The meta information includes:
 /// 1. `afi`: address family ([Afi]): IPv4 or IPv6,
 /// 2. `asn_len`: AS number length ([AsnLength]): 16 or 32 bits.
-#[derive(Debug, Clone, Serialize, Copy)]
+#[derive(Debug, Clone, Serialize, Copy, PartialEq, Eq)]
 pub struct AddrMeta {
     pub afi: Afi,
     pub asn_len: AsnLength,
 }
 
+impl AddrMeta {
+    pub fn new(afi: Afi, asn_len: AsnLength) -> AddrMeta {
+        AddrMeta { afi, asn_len }
+    }
+
+    /// Decode the peer type flags of a TABLE_DUMP_V2 peer entry
+    /// ([RFC6396 section 4.3.1](https://datatracker.ietf.org/doc/html/rfc6396#section-4.3.1)):
+    /// bit 7 (`0x01`) marks an IPv6 peer address and bit 6 (`0x02`) a 32-bit peer ASN.
+    pub fn from_peer_type(peer_type: u8) -> AddrMeta {
+        AddrMeta {
+            afi: match peer_type & 0x01 != 0 {
+                true => Afi::Ipv6,
+                false => Afi::Ipv4,
+            },
+            asn_len: match peer_type & 0x02 != 0 {
+                true => AsnLength::Bits32,
+                false => AsnLength::Bits16,
+            },
+        }
+    }
+}
+
 /// AS number length: 16 or 32 bits.
 #[derive(Debug, Clone, Serialize, Copy, Deserialize, PartialEq, Eq)]
 pub enum AsnLength {
@@ -41,6 +67,25 @@ impl PartialEq for Asn {
     }
 }
 
+impl Hash for Asn {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // consistent with `PartialEq`: the ASN length does not take part in equality
+        self.asn.hash(state);
+    }
+}
+
+impl PartialOrd for Asn {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Asn {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.asn.cmp(&other.asn)
+    }
+}
+
 impl PartialEq<i32> for Asn {
     fn eq(&self, other: &i32) -> bool {
         self.asn as i32==*other
@@ -65,28 +110,104 @@ impl From<i32> for Asn {
     }
 }
 
-impl Into<i32> for Asn {
-    fn into(self) -> i32 {
-        self.asn as i32
+impl From<Asn> for i32 {
+    fn from(val: Asn) -> Self {
+        val.asn as i32
     }
 }
 
-impl Into<u32> for Asn {
-    fn into(self) -> u32 {
-        self.asn
+impl From<Asn> for u32 {
+    fn from(val: Asn) -> Self {
+        val.asn
     }
 }
 
+/// Category of an ASN by IANA special-purpose registrations.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Eq, Hash)]
+pub enum AsnClass {
+    /// Publicly routable ASN.
+    Public,
+    /// 16-bit private use, `64512-65534` ([RFC 6996](https://datatracker.ietf.org/doc/html/rfc6996)).
+    Private16,
+    /// 32-bit private use, `4200000000-4294967294` ([RFC 6996](https://datatracker.ietf.org/doc/html/rfc6996)).
+    Private32,
+    /// Documentation, `64496-64511` and `65536-65551` ([RFC 5398](https://datatracker.ietf.org/doc/html/rfc5398)).
+    Documentation,
+    /// Reserved, `0` ([RFC 7607](https://datatracker.ietf.org/doc/html/rfc7607)), `65535` and
+    /// `4294967295` ([RFC 7300](https://datatracker.ietf.org/doc/html/rfc7300)), and `65552-131071`.
+    Reserved,
+    /// AS_TRANS, `23456` ([RFC 6793](https://datatracker.ietf.org/doc/html/rfc6793)).
+    AsTrans,
+}
+
+/// Classify an ASN into an [AsnClass].
+pub fn classify_asn(asn: Asn) -> AsnClass {
+    match asn.asn {
+        0 | 65535 | 65552..=131071 | 4294967295 => AsnClass::Reserved,
+        23456 => AsnClass::AsTrans,
+        64496..=64511 | 65536..=65551 => AsnClass::Documentation,
+        64512..=65534 => AsnClass::Private16,
+        4200000000..=4294967294 => AsnClass::Private32,
+        _ => AsnClass::Public,
+    }
+}
+
+/// Check whether an ASN falls into an IANA reserved, private-use or documentation range, i.e.
+/// whether it is not [AsnClass::Public]. See [AsnClass] for the ranges.
+pub fn is_reserved_asn(asn: Asn) -> bool {
+    classify_asn(asn) != AsnClass::Public
+}
+
 impl Serialize for Asn {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
-        Ok( serializer.serialize_u32(self.asn)?)
+        serializer.serialize_u32(self.asn)
+    }
+}
+
+impl<'de> Deserialize<'de> for Asn {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        Ok(Asn::from(u32::deserialize(deserializer)?))
+    }
+}
+
+/// Format an ASN in asdot notation ([RFC 5396](https://datatracker.ietf.org/doc/html/rfc5396)).
+///
+/// ASNs above 65535 are written as `high.low`, the high and low order 16-bit values, e.g.
+/// `4200000000` becomes `64086.59904`. Smaller ASNs are written as plain numbers. Note that the
+/// [Display] of [Asn] uses the asplain notation for all ASNs.
+pub fn asn_to_asdot(asn: Asn) -> String {
+    match asn.asn {
+        0..=65535 => asn.asn.to_string(),
+        v => format!("{}.{}", v >> 16, v & 0xffff),
+    }
+}
+
+/// Parse an ASN in asdot notation, e.g. `1.2` into `65538`.
+///
+/// Plain numbers are accepted as well, so both asdot and asplain strings can be parsed.
+pub fn asdot_to_asn(s: &str) -> Result<Asn, BgpModelsError> {
+    let err = || BgpModelsError::AsnParsingError(s.to_string());
+    let mut parts = s.splitn(2, '.');
+    match (parts.next(), parts.next()) {
+        (Some(high), Some(low)) => {
+            let high = u16::from_str(high).map_err(|_| err())?;
+            let low = u16::from_str(low).map_err(|_| err())?;
+            Ok(Asn::from(((high as u32) << 16) | low as u32))
+        }
+        _ => Ok(Asn::from(u32::from_str(s).map_err(|_| err())?)),
     }
 }
 
+/// Convert a BGP Identifier (Router ID) in its 4-octet integer form into an [Ipv4Addr], the
+/// conventional dotted-quad representation.
+pub fn bgp_id_to_ipv4(id: u32) -> Ipv4Addr {
+    Ipv4Addr::from(id)
+}
+
 /// AFI -- Address Family Identifier
 ///
 /// https://www.iana.org/assignments/address-family-numbers/address-family-numbers.xhtml
-#[derive(Debug, PartialEq, Primitive, Clone, Copy, Serialize, Eq)]
+#[derive(Debug, PartialEq, Primitive, Clone, Copy, Serialize, Eq, Hash)]
 pub enum Afi {
     Ipv4 = 1,
     Ipv6 = 2,
@@ -94,37 +215,141 @@ pub enum Afi {
 
 /// SAFI -- Subsequent Address Family Identifier
 ///
-/// SAFI can be: Unicast, Multicast, or both.
-#[derive(Debug, PartialEq, Primitive, Clone, Copy, Serialize, Eq)]
+/// SAFI can be: Unicast, Multicast, or both, as well as MPLS-labeled and VPN families.
+///
+/// <https://www.iana.org/assignments/safi-namespace/safi-namespace.xhtml>
+#[derive(Debug, PartialEq, Primitive, Clone, Copy, Serialize, Eq, Hash)]
 pub enum Safi {
     Unicast = 1,
     Multicast = 2,
     UnicastMulticast = 3,
+    /// <https://datatracker.ietf.org/doc/html/rfc8277>
+    MplsLabel = 4,
+    /// <https://datatracker.ietf.org/doc/html/rfc4364>
+    MplsVpn = 128,
+    /// <https://datatracker.ietf.org/doc/html/rfc8955>
+    Flowspec = 133,
+    FlowspecVpn = 134,
 }
 
 /// enum that represents the type of the next hop address.
 ///
 /// [NextHopAddress] is used when parsing for next hops in [Nlri].
-#[derive(Debug, PartialEq, Copy, Clone, Serialize, Eq)]
+#[derive(Debug, PartialEq, Copy, Clone, Serialize, Eq, Hash)]
 pub enum NextHopAddress {
     Ipv4(Ipv4Addr),
     Ipv6(Ipv6Addr),
     Ipv6LinkLocal(Ipv6Addr, Ipv6Addr),
 }
 
+impl NextHopAddress {
+    pub fn is_ipv6(&self) -> bool {
+        !matches!(self, NextHopAddress::Ipv4(_))
+    }
+
+    /// Check whether the next hop carries a link-local address in addition to the global one.
+    pub fn is_link_local(&self) -> bool {
+        matches!(self, NextHopAddress::Ipv6LinkLocal(_, _))
+    }
+}
+
+/// Convert a next hop into an [IpAddr]. For [NextHopAddress::Ipv6LinkLocal], the global address
+/// is used and the link-local address is dropped.
+impl From<NextHopAddress> for IpAddr {
+    fn from(next_hop: NextHopAddress) -> Self {
+        match next_hop {
+            NextHopAddress::Ipv4(v) => IpAddr::V4(v),
+            NextHopAddress::Ipv6(v) => IpAddr::V6(v),
+            NextHopAddress::Ipv6LinkLocal(global, _) => IpAddr::V6(global),
+        }
+    }
+}
+
+impl From<IpAddr> for NextHopAddress {
+    fn from(ip: IpAddr) -> Self {
+        match ip {
+            IpAddr::V4(v) => NextHopAddress::Ipv4(v),
+            IpAddr::V6(v) => NextHopAddress::Ipv6(v),
+        }
+    }
+}
+
 /// A representation of a IP prefix with optional path ID.
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+///
+/// Prefixes are totally ordered so that they can be used as [BTreeMap](std::collections::BTreeMap) keys.
+/// The ordering compares, in turn:
+/// 1. IP version: all IPv4 prefixes sort before IPv6 prefixes,
+/// 2. network address bytes,
+/// 3. prefix length: less specific prefixes sort first,
+/// 4. `path_id`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
 pub struct NetworkPrefix {
     pub prefix: IpNetwork,
     pub path_id: u32,
 }
 
+impl PartialOrd for NetworkPrefix {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for NetworkPrefix {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.prefix.is_ipv6().cmp(&other.prefix.is_ipv6())
+            .then_with(|| self.prefix.network().cmp(&other.prefix.network()))
+            .then_with(|| self.prefix.prefix().cmp(&other.prefix.prefix()))
+            .then_with(|| self.path_id.cmp(&other.path_id))
+            // keep ordering consistent with `Eq` for prefixes with host bits set
+            .then_with(|| self.prefix.cmp(&other.prefix))
+    }
+}
+
 impl Serialize for NetworkPrefix {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
         serializer.serialize_str(self.to_string().as_str())
     }
 }
 
+impl<'de> Deserialize<'de> for NetworkPrefix {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let s = String::deserialize(deserializer)?;
+        NetworkPrefix::from_str(s.as_str()).map_err(de::Error::custom)
+    }
+}
+
+/// Remove duplicated prefixes in place, keeping the first occurrence of each.
+///
+/// Two prefixes are duplicates only if both the network and the `path_id` are equal, so distinct
+/// ADD-PATH entries of the same network are kept.
+pub fn dedup_prefixes(prefixes: &mut Vec<NetworkPrefix>) {
+    let mut seen: HashSet<NetworkPrefix> = HashSet::new();
+    prefixes.retain(|p| seen.insert(*p));
+}
+
+/// Count the distinct networks of each IP version, returning `(ipv4_count, ipv6_count)`.
+///
+/// Unlike [dedup_prefixes], the `path_id` is ignored, so ADD-PATH entries of the same network
+/// are counted once.
+pub fn count_by_family(prefixes: &[NetworkPrefix]) -> (usize, usize) {
+    let networks: HashSet<IpNetwork> = prefixes.iter().map(|p| p.prefix).collect();
+    let ipv4_count = networks.iter().filter(|n| n.is_ipv4()).count();
+    (ipv4_count, networks.len() - ipv4_count)
+}
+
+/// Count prefixes by IP version (`4` or `6`) and prefix length, keyed by `(version, length)`.
+///
+/// Every entry is counted, so duplicates should be removed beforehand (e.g. with
+/// [dedup_prefixes]) if only distinct prefixes are of interest.
+pub fn prefix_length_histogram(prefixes: &[NetworkPrefix]) -> BTreeMap<(u8, u8), usize> {
+    let mut histogram = BTreeMap::new();
+    for p in prefixes {
+        let version = if p.prefix.is_ipv4() { 4 } else { 6 };
+        *histogram.entry((version, p.prefix.prefix())).or_default() += 1;
+    }
+    histogram
+}
+
 impl FromStr for NetworkPrefix {
     type Err = BgpModelsError;
 
@@ -143,6 +368,23 @@ impl NetworkPrefix {
     pub fn new(prefix: IpNetwork, path_id: u32) -> NetworkPrefix {
         NetworkPrefix { prefix, path_id }
     }
+
+    /// Check whether both prefixes are the same network, ignoring `path_id`.
+    ///
+    /// Use `==` to also compare `path_id`, e.g. for ADD-PATH routes.
+    pub fn same_network(&self, other: &NetworkPrefix) -> bool {
+        self.prefix == other.prefix
+    }
+
+    /// Check whether `other` is equal to or more specific than this prefix.
+    ///
+    /// Prefixes of different address families never contain each other. `path_id` is ignored.
+    pub fn contains(&self, other: &NetworkPrefix) -> bool {
+        if self.prefix.is_ipv4() != other.prefix.is_ipv4() {
+            return false
+        }
+        self.prefix.prefix() <= other.prefix.prefix() && self.prefix.contains(other.prefix.network())
+    }
 }
 
 impl Display for NetworkPrefix {
@@ -151,9 +393,255 @@ impl Display for NetworkPrefix {
     }
 }
 
+impl Display for Safi {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Safi::Unicast => "unicast",
+            Safi::Multicast => "multicast",
+            Safi::UnicastMulticast => "unicast-multicast",
+            Safi::MplsLabel => "mpls-label",
+            Safi::MplsVpn => "mpls-vpn",
+            Safi::Flowspec => "flowspec",
+            Safi::FlowspecVpn => "flowspec-vpn",
+        })
+    }
+}
+
+/// Parse SAFI from its name (e.g. `unicast`), its numeric value (e.g. `1`), or the
+/// `safi-<n>` form (e.g. `safi-1`).
+impl FromStr for Safi {
+    type Err = BgpModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unicast" => Ok(Safi::Unicast),
+            "multicast" => Ok(Safi::Multicast),
+            "unicast-multicast" => Ok(Safi::UnicastMulticast),
+            "mpls-label" => Ok(Safi::MplsLabel),
+            "mpls-vpn" => Ok(Safi::MplsVpn),
+            "flowspec" => Ok(Safi::Flowspec),
+            "flowspec-vpn" => Ok(Safi::FlowspecVpn),
+            _ => {
+                let num = s.strip_prefix("safi-").unwrap_or(s);
+                match num.parse::<u8>().ok().and_then(Safi::from_u8) {
+                    Some(safi) => Ok(safi),
+                    None => Err(BgpModelsError::SafiParsingError(s.to_string())),
+                }
+            }
+        }
+    }
+}
+
 impl Display for Asn {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.asn)
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefix_ordering() {
+        let mut prefixes: Vec<NetworkPrefix> = [
+            "2001:db8::/32",
+            "10.0.0.0/16",
+            "10.0.0.0/8",
+            "::/0",
+            "1.1.1.0/24",
+        ].iter().map(|p| NetworkPrefix::from_str(p).unwrap()).collect();
+        prefixes.push(NetworkPrefix::new(IpNetwork::from_str("10.0.0.0/8").unwrap(), 1));
+        prefixes.sort();
+
+        let sorted: Vec<(String, u32)> = prefixes.iter().map(|p| (p.to_string(), p.path_id)).collect();
+        assert_eq!(sorted, vec![
+            ("1.1.1.0/24".to_string(), 0),
+            ("10.0.0.0/8".to_string(), 0),
+            ("10.0.0.0/8".to_string(), 1),
+            ("10.0.0.0/16".to_string(), 0),
+            ("::/0".to_string(), 0),
+            ("2001:db8::/32".to_string(), 0),
+        ]);
+    }
+
+    #[test]
+    fn test_bgp_id_to_ipv4() {
+        assert_eq!(bgp_id_to_ipv4(0x0a000001), Ipv4Addr::new(10, 0, 0, 1));
+        assert_eq!(bgp_id_to_ipv4(0xc0a80101).to_string(), "192.168.1.1");
+    }
+
+    #[test]
+    fn test_next_hop_ip_conversion() {
+        for s in ["10.0.0.1", "2001:db8::1"] {
+            let ip = IpAddr::from_str(s).unwrap();
+            let next_hop = NextHopAddress::from(ip);
+            assert_eq!(next_hop.is_ipv6(), ip.is_ipv6());
+            assert!(!next_hop.is_link_local());
+            assert_eq!(IpAddr::from(next_hop), ip);
+        }
+
+        let global = Ipv6Addr::from_str("2001:db8::1").unwrap();
+        let next_hop = NextHopAddress::Ipv6LinkLocal(global, Ipv6Addr::from_str("fe80::1").unwrap());
+        assert!(next_hop.is_ipv6());
+        assert!(next_hop.is_link_local());
+        let ip: IpAddr = next_hop.into();
+        assert_eq!(ip, IpAddr::V6(global));
+    }
+
+    #[test]
+    fn test_addr_meta_from_peer_type() {
+        assert_eq!(AddrMeta::from_peer_type(0), AddrMeta::new(Afi::Ipv4, AsnLength::Bits16));
+        assert_eq!(AddrMeta::from_peer_type(1), AddrMeta::new(Afi::Ipv6, AsnLength::Bits16));
+        assert_eq!(AddrMeta::from_peer_type(2), AddrMeta::new(Afi::Ipv4, AsnLength::Bits32));
+        assert_eq!(AddrMeta::from_peer_type(3), AddrMeta::new(Afi::Ipv6, AsnLength::Bits32));
+    }
+
+    #[test]
+    fn test_same_network() {
+        let p = NetworkPrefix::from_str("10.0.0.0/8").unwrap();
+        let p_path1 = NetworkPrefix::new(p.prefix, 1);
+        assert!(p.same_network(&p_path1));
+        assert_ne!(p, p_path1);
+        assert!(!p.same_network(&NetworkPrefix::from_str("10.0.0.0/16").unwrap()));
+    }
+
+    #[test]
+    fn test_dedup_prefixes() {
+        let p = NetworkPrefix::from_str("10.0.0.0/8").unwrap();
+        let p_path1 = NetworkPrefix::new(p.prefix, 1);
+        let other = NetworkPrefix::from_str("1.1.1.0/24").unwrap();
+
+        let mut prefixes = vec![p, other, p, p_path1, other];
+        dedup_prefixes(&mut prefixes);
+        assert_eq!(prefixes, vec![p, other, p_path1]);
+    }
+
+    #[test]
+    fn test_count_by_family() {
+        let p = NetworkPrefix::from_str("10.0.0.0/8").unwrap();
+        let prefixes = vec![
+            p,
+            NetworkPrefix::new(p.prefix, 1),
+            NetworkPrefix::from_str("1.1.1.0/24").unwrap(),
+            NetworkPrefix::from_str("2001:db8::/32").unwrap(),
+            NetworkPrefix::from_str("2001:db8::/48").unwrap(),
+            NetworkPrefix::from_str("2001:db8::/48").unwrap(),
+        ];
+        assert_eq!(count_by_family(&prefixes), (2, 2));
+        assert_eq!(count_by_family(&[]), (0, 0));
+    }
+
+    #[test]
+    fn test_prefix_length_histogram() {
+        let prefixes: Vec<NetworkPrefix> = [
+            "1.1.1.0/24", "8.8.8.0/24", "10.0.0.0/8", "2001:db8::/48", "2001:db8:1::/48", "2001:db8:2::/48",
+        ].iter().map(|s| NetworkPrefix::from_str(s).unwrap()).collect();
+        let histogram = prefix_length_histogram(&prefixes);
+        assert_eq!(histogram.into_iter().collect::<Vec<((u8, u8), usize)>>(),
+                   vec![((4, 8), 1), ((4, 24), 2), ((6, 48), 3)]);
+        assert!(prefix_length_histogram(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_prefix_contains() {
+        let p16 = NetworkPrefix::from_str("192.168.0.0/16").unwrap();
+        let p24 = NetworkPrefix::from_str("192.168.1.0/24").unwrap();
+        let other = NetworkPrefix::from_str("10.0.0.0/24").unwrap();
+        let v6 = NetworkPrefix::from_str("::/0").unwrap();
+
+        // exact match
+        assert!(p16.contains(&p16));
+        // strict subset
+        assert!(p16.contains(&p24));
+        assert!(!p24.contains(&p16));
+        // disjoint
+        assert!(!p16.contains(&other));
+        // cross-family
+        assert!(!v6.contains(&p24));
+        assert!(!NetworkPrefix::from_str("0.0.0.0/0").unwrap().contains(&v6));
+    }
+
+    #[test]
+    fn test_safi_str() {
+        for (safi, name) in [
+            (Safi::Unicast, "unicast"),
+            (Safi::Multicast, "multicast"),
+            (Safi::UnicastMulticast, "unicast-multicast"),
+            (Safi::MplsLabel, "mpls-label"),
+            (Safi::MplsVpn, "mpls-vpn"),
+            (Safi::Flowspec, "flowspec"),
+            (Safi::FlowspecVpn, "flowspec-vpn"),
+        ] {
+            assert_eq!(safi.to_string(), name);
+            assert_eq!(Safi::from_str(name).unwrap(), safi);
+        }
+        assert_eq!(Safi::from_str("2").unwrap(), Safi::Multicast);
+        assert_eq!(Safi::from_str("safi-3").unwrap(), Safi::UnicastMulticast);
+        assert!(Safi::from_str("safi-99").is_err());
+        assert!(Safi::from_str("anycast").is_err());
+    }
+
+    #[test]
+    fn test_safi_vpn_values() {
+        assert_eq!(Safi::from_u8(4), Some(Safi::MplsLabel));
+        assert_eq!(Safi::from_u8(128), Some(Safi::MplsVpn));
+        assert_eq!(Safi::from_u8(133), Some(Safi::Flowspec));
+        assert_eq!(Safi::from_u8(134), Some(Safi::FlowspecVpn));
+    }
+
+    #[test]
+    fn test_classify_asn() {
+        let cases = [
+            (0u32, AsnClass::Reserved),
+            (1, AsnClass::Public),
+            (23455, AsnClass::Public),
+            (23456, AsnClass::AsTrans),
+            (64495, AsnClass::Public),
+            (64496, AsnClass::Documentation),
+            (64511, AsnClass::Documentation),
+            (64512, AsnClass::Private16),
+            (65534, AsnClass::Private16),
+            (65535, AsnClass::Reserved),
+            (65536, AsnClass::Documentation),
+            (65551, AsnClass::Documentation),
+            (65552, AsnClass::Reserved),
+            (131071, AsnClass::Reserved),
+            (131072, AsnClass::Public),
+            (4199999999, AsnClass::Public),
+            (4200000000, AsnClass::Private32),
+            (4294967294, AsnClass::Private32),
+            (4294967295, AsnClass::Reserved),
+        ];
+        for (asn, class) in cases {
+            assert_eq!(classify_asn(asn.into()), class, "ASN {}", asn);
+        }
+    }
+
+    #[test]
+    fn test_asdot() {
+        let asn: Asn = 4200000000u32.into();
+        assert_eq!(asn_to_asdot(asn), "64086.59904");
+        assert_eq!(asdot_to_asn("64086.59904").unwrap(), asn);
+
+        let asn = asdot_to_asn("1.2").unwrap();
+        assert_eq!(asn, 65538u32);
+        assert_eq!(asn_to_asdot(asn), "1.2");
+
+        assert_eq!(asn_to_asdot(Asn::from(65000)), "65000");
+        assert_eq!(asdot_to_asn("65000").unwrap(), 65000u32);
+        assert!(asdot_to_asn("65536.1").is_err());
+        assert!(asdot_to_asn("1.").is_err());
+        assert!(asdot_to_asn("AS1").is_err());
+    }
+
+    #[test]
+    fn test_is_reserved_asn() {
+        for asn in [0, 23456, 64496, 64511, 64512, 65534, 65535, 65536, 131071, 4200000000, 4294967295u32] {
+            assert!(is_reserved_asn(asn.into()));
+        }
+        for asn in [1, 13335, 64495, 131072, 4199999999u32] {
+            assert!(!is_reserved_asn(asn.into()));
+        }
+    }
+}